@@ -0,0 +1,47 @@
+/// `text` with everything outside ASCII replaced, for `--ascii-only`: the
+/// glyphs sl draws with get stand-ins of their own, other characters their
+/// closest transliteration (`é` → `e`, `ß` → `ss`), and anything left `?`
+pub fn transliterate(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    for c in text.chars() {
+        if c.is_ascii() {
+            out.push(c);
+            continue;
+        }
+        match stand_in(c).or_else(|| deunicode::deunicode_char(c)) {
+            Some(ascii) => out.push_str(ascii),
+            None => out.push('?'),
+        }
+    }
+    out
+}
+
+// Where the transliteration reads wrong in sl's own output
+fn stand_in(c: char) -> Option<&'static str> {
+    match c {
+        '→' => Some("->"),
+        '⚠' => Some("!"),
+        '═' => Some("="),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transliterate() {
+        assert_eq!(
+            transliterate("⚠️  Scripts run each other in a cycle: a → b → a"),
+            "!  Scripts run each other in a cycle: a -> b -> a"
+        );
+        assert_eq!(transliterate("╔══╗\n║ x… ║\n└──┘"), "+==+\n| x... |\n+--+");
+        assert_eq!(
+            transliterate("echo 'démarré' | grüßen"),
+            "echo 'demarre' | grussen"
+        );
+        assert_eq!(transliterate("\u{10ffff}"), "?");
+        assert_eq!(transliterate("\x1b[32mok\x1b[0m"), "\x1b[32mok\x1b[0m");
+    }
+}
@@ -0,0 +1,188 @@
+use anyhow::{bail, Context, Result};
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use crate::package_manager::PackageManager;
+use crate::shell;
+
+/// Where the `srcs` of the rules point without `--wrapper-dir`
+pub const DEFAULT_WRAPPER_DIR: &str = "npm_scripts";
+
+/// BUILD file entries, an `sh_binary` per script whose source is its wrapper
+/// in `wrapper_dir` (relative to the BUILD file) and which depends on the
+/// root `node_modules`
+pub fn build_file(names: &[&str], wrapper_dir: &str) -> String {
+    names
+        .iter()
+        .map(|name| {
+            let src = format!("{}/{}", wrapper_dir.trim_end_matches('/'), file_name(name));
+            format!(
+                "sh_binary(\n    name = {},\n    srcs = [{}],\n    data = [\"//:node_modules\"],\n)\n",
+                string(&target_name(name)),
+                string(&src)
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// The shell script a rule runs: the script through `manager`, from the
+/// workspace root `bazel run` reports, passing on any arguments (after a
+/// `--` for npm, so they reach the script rather than npm)
+pub fn wrapper(name: &str, manager: PackageManager) -> String {
+    const ARGS: &str = "\"$@\"";
+    let exec = std::iter::once(manager.name().to_string())
+        .chain(manager.run_args(name, &[ARGS.to_string()]))
+        .map(|word| {
+            if word == ARGS {
+                word
+            } else {
+                shell::quote(&word)
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ");
+    format!(
+        "#!/bin/sh\n# {}, for `bazel run //:{}`\ncd \"$BUILD_WORKSPACE_DIRECTORY\" && exec {}\n",
+        manager.command_line(name),
+        target_name(name),
+        exec
+    )
+}
+
+/// Write each script's wrapper to `dir`, executable, replacing any with the
+/// same name. Returns how many were written.
+pub fn export_wrappers(dir: &Path, names: &[&str], manager: PackageManager) -> Result<usize> {
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    for name in names {
+        let path = dir.join(file_name(name));
+        fs::write(&path, wrapper(name, manager))
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            fs::set_permissions(&path, fs::Permissions::from_mode(0o755))
+                .with_context(|| format!("Failed to make {} executable", path.display()))?;
+        }
+    }
+    Ok(names.len())
+}
+
+/// Fail if two scripts would share a target, and so a wrapper too:
+/// `build:css` and `build_css` are both `build_css`
+pub fn check_target_names(names: &[&str]) -> Result<()> {
+    let mut taken = HashMap::new();
+    for name in names {
+        if let Some(other) = taken.insert(target_name(name), name) {
+            bail!(
+                "`{}` and `{}` would both be Bazel target {}",
+                other,
+                name,
+                target_name(name)
+            );
+        }
+    }
+    Ok(())
+}
+
+/// The wrapper's file name, `build_css.sh` for `build:css`
+pub fn file_name(name: &str) -> String {
+    format!("{}.sh", target_name(name))
+}
+
+// Bazel target names can't have `:` and friends; keep to the safe set
+fn target_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' | '.' => c,
+            _ => '_',
+        })
+        .collect()
+}
+
+// A Starlark string literal
+fn string(text: &str) -> String {
+    format!("\"{}\"", text.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_file() {
+        let build = build_file(&["build", "test:unit"], "tools/npm/");
+        assert_eq!(
+            build,
+            "sh_binary(\n\
+             \x20   name = \"build\",\n\
+             \x20   srcs = [\"tools/npm/build.sh\"],\n\
+             \x20   data = [\"//:node_modules\"],\n\
+             )\n\
+             \n\
+             sh_binary(\n\
+             \x20   name = \"test_unit\",\n\
+             \x20   srcs = [\"tools/npm/test_unit.sh\"],\n\
+             \x20   data = [\"//:node_modules\"],\n\
+             )\n"
+        );
+
+        // Every rule a call with balanced brackets and `key = value,` lines
+        for rule in build.split("\n\n") {
+            assert!(rule.starts_with("sh_binary(\n") && rule.trim_end().ends_with("\n)"));
+            assert_eq!(rule.matches('(').count(), rule.matches(')').count());
+            assert_eq!(rule.matches('[').count(), rule.matches(']').count());
+            for line in rule.lines().skip(1).filter(|line| *line != ")") {
+                let (key, value) = line.trim().split_once(" = ").unwrap();
+                assert!(key.chars().all(|c| c.is_ascii_lowercase()));
+                assert!(value.ends_with(','));
+            }
+        }
+    }
+
+    #[test]
+    fn test_wrapper() {
+        assert_eq!(
+            wrapper("test:unit", PackageManager::Pnpm),
+            "#!/bin/sh\n\
+             # pnpm run test:unit, for `bazel run //:test_unit`\n\
+             cd \"$BUILD_WORKSPACE_DIRECTORY\" && exec pnpm run test:unit \"$@\"\n"
+        );
+        // npm itself would take `bazel run //:test -- --watch`'s --watch
+        assert!(wrapper("test", PackageManager::Npm).ends_with("&& exec npm run test -- \"$@\"\n"));
+        assert_eq!(string(r#"say "hi" \o/"#), r#""say \"hi\" \\o/""#);
+    }
+
+    #[test]
+    fn test_check_target_names() {
+        assert!(check_target_names(&["build", "build:css", "lint"]).is_ok());
+        assert_eq!(
+            check_target_names(&["build:css", "build_css"])
+                .unwrap_err()
+                .to_string(),
+            "`build:css` and `build_css` would both be Bazel target build_css"
+        );
+    }
+
+    #[test]
+    fn test_export_wrappers() {
+        let dir = tempfile::tempdir().unwrap();
+        let out_dir = dir.path().join("npm_scripts");
+        assert_eq!(
+            export_wrappers(&out_dir, &["build", "lint:fix"], PackageManager::Npm).unwrap(),
+            2
+        );
+        let wrapper = fs::read_to_string(out_dir.join("lint_fix.sh")).unwrap();
+        assert!(wrapper.contains("exec npm run lint:fix -- \"$@\""));
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let mode = fs::metadata(out_dir.join("build.sh"))
+                .unwrap()
+                .permissions()
+                .mode();
+            assert_eq!(mode & 0o111, 0o111);
+        }
+    }
+}
@@ -0,0 +1,75 @@
+use std::collections::{BTreeSet, HashSet};
+use std::fs;
+use std::path::Path;
+
+use crate::shell;
+
+// npm writes Windows shims next to each binary (`tsc`, `tsc.cmd`, `tsc.ps1`)
+const SHIM_EXTENSIONS: &[&str] = &["cmd", "ps1", "exe"];
+
+/// Names of the executables in a `node_modules/.bin` directory, sorted and
+/// with Windows shims folded into their binary. A missing directory has none.
+pub fn scan_bin_dir(path: &Path) -> Vec<String> {
+    let Ok(entries) = fs::read_dir(path) else {
+        return Vec::new();
+    };
+    let names: BTreeSet<String> = entries
+        .filter_map(Result::ok)
+        .map(|entry| entry.file_name().to_string_lossy().to_string())
+        .filter(|name| !name.starts_with('.'))
+        .map(|name| match name.rsplit_once('.') {
+            Some((stem, ext)) if SHIM_EXTENSIONS.contains(&ext.to_lowercase().as_str()) => {
+                stem.to_string()
+            }
+            _ => name,
+        })
+        .collect();
+    names.into_iter().collect()
+}
+
+/// The binaries some script runs directly
+pub fn used_in_scripts<'a>(bins: &'a [String], scripts: &[(String, String)]) -> HashSet<&'a str> {
+    let programs: HashSet<String> = scripts
+        .iter()
+        .flat_map(|(_, command)| shell::programs(command))
+        .collect();
+    bins.iter()
+        .filter(|bin| programs.contains(bin.as_str()))
+        .map(String::as_str)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[cfg(unix)]
+    #[test]
+    fn test_scan_bin_dir() {
+        use std::os::unix::fs::symlink;
+
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("node_modules/.bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        for bin in ["tsc", "eslint", "jest"] {
+            let target = dir.path().join(format!("{}.js", bin));
+            fs::write(&target, "").unwrap();
+            symlink(&target, bin_dir.join(bin)).unwrap();
+        }
+        fs::write(bin_dir.join("tsc.cmd"), "").unwrap();
+        fs::write(bin_dir.join(".package-lock.json"), "").unwrap();
+
+        let bins = scan_bin_dir(&bin_dir);
+        assert_eq!(bins, ["eslint", "jest", "tsc"]);
+        assert!(scan_bin_dir(&dir.path().join("missing")).is_empty());
+
+        let scripts = [
+            (
+                "build".to_string(),
+                "NODE_ENV=production tsc -p .".to_string(),
+            ),
+            ("lint".to_string(), "echo eslint".to_string()),
+        ];
+        assert_eq!(used_in_scripts(&bins, &scripts), HashSet::from(["tsc"]));
+    }
+}
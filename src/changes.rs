@@ -0,0 +1,355 @@
+use anyhow::{Context, Result};
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::color::*;
+use crate::history;
+
+/// How the scripts changed since the previous listing
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct ScriptDiff {
+    pub added: HashSet<String>,
+    pub changed: HashSet<String>,
+    /// Scripts that are gone, with the command they had, sorted by name
+    pub removed: Vec<(String, String)>,
+}
+
+impl ScriptDiff {
+    /// `+` for new scripts, `~` for changed ones
+    pub fn sigil(&self, name: &str) -> Option<char> {
+        if self.added.contains(name) {
+            Some('+')
+        } else if self.changed.contains(name) {
+            Some('~')
+        } else {
+            None
+        }
+    }
+}
+
+pub fn diff_with_previous(new: &[(String, String)], old: &[(String, String)]) -> ScriptDiff {
+    let old_commands: HashMap<&str, &str> = old
+        .iter()
+        .map(|(name, command)| (name.as_str(), command.as_str()))
+        .collect();
+    let new_names: HashSet<&str> = new.iter().map(|(name, _)| name.as_str()).collect();
+
+    let mut diff = ScriptDiff::default();
+    for (name, command) in new {
+        match old_commands.get(name.as_str()) {
+            None => {
+                diff.added.insert(name.clone());
+            }
+            Some(old) if old != command => {
+                diff.changed.insert(name.clone());
+            }
+            Some(_) => {}
+        }
+    }
+    diff.removed = old
+        .iter()
+        .filter(|(name, _)| !new_names.contains(name.as_str()))
+        .cloned()
+        .collect();
+    diff.removed.sort();
+    diff
+}
+
+/// Hex digits in a `command_hash`
+pub const HASH_LEN: usize = 8;
+
+/// The first few hex digits of the SHA-256 of `command`, exactly as written
+pub fn command_hash(command: &str) -> String {
+    Sha256::digest(command.as_bytes())
+        .iter()
+        .take(HASH_LEN / 2)
+        .map(|byte| format!("{:02x}", byte))
+        .collect()
+}
+
+/// How the scripts of several package.json files compare
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct DiffResult {
+    /// Scripts missing from some files or with different commands across
+    /// them, by name, with the command in each file (`None` where missing)
+    pub differing: Vec<(String, Vec<Option<String>>)>,
+    /// Scripts with the same command in every file, by name
+    pub identical: Vec<String>,
+}
+
+/// Compare the script sets of several files, by script name
+pub fn compute_set_diff(sets: &[Vec<(String, String)>]) -> DiffResult {
+    let names: BTreeSet<&str> = sets
+        .iter()
+        .flatten()
+        .map(|(name, _)| name.as_str())
+        .collect();
+
+    let mut diff = DiffResult::default();
+    for name in names {
+        let commands: Vec<Option<String>> = sets
+            .iter()
+            .map(|set| {
+                set.iter()
+                    .find(|(n, _)| n == name)
+                    .map(|(_, command)| command.clone())
+            })
+            .collect();
+        if commands
+            .iter()
+            .all(|command| command.is_some() && *command == commands[0])
+        {
+            diff.identical.push(name.to_string());
+        } else {
+            diff.differing.push((name.to_string(), commands));
+        }
+    }
+    diff
+}
+
+/// `--list-only-changed`: the differing scripts with their command in each
+/// file, and how many were left out for being identical
+pub fn print_set_diff(
+    out: &mut impl Write,
+    paths: &[PathBuf],
+    diff: &DiffResult,
+) -> io::Result<()> {
+    let labels: Vec<String> = paths
+        .iter()
+        .map(|path| format!("{}:", path.display()))
+        .collect();
+    let width = labels
+        .iter()
+        .map(|label| label.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    writeln!(out)?;
+    for (name, commands) in &diff.differing {
+        writeln!(out, "    - {}", name.truecolor(128, 128, 128))?;
+        for (label, command) in labels.iter().zip(commands) {
+            match command {
+                Some(command) => writeln!(out, "      {:<width$} {}", label, command)?,
+                None => writeln!(out, "      {:<width$} {}", label, "(missing)".dimmed())?,
+            }
+        }
+    }
+    if diff.differing.is_empty() {
+        writeln!(out, "   {}", "No differences".green())?;
+    }
+
+    writeln!(out)?;
+    if !diff.identical.is_empty() {
+        let footer = format!(
+            "   {} script(s) are the same in every file",
+            diff.identical.len()
+        );
+        writeln!(out, "{}", footer.dimmed())?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// `--watch-diff`: the scripts added (+), changed (~, with the command they
+/// had) and removed (-) between two renders
+pub fn print_render_diff(
+    out: &mut impl Write,
+    new: &[(String, String)],
+    old: &[(String, String)],
+) -> io::Result<()> {
+    let diff = diff_with_previous(new, old);
+    let old_commands: HashMap<&str, &str> = old
+        .iter()
+        .map(|(name, command)| (name.as_str(), command.as_str()))
+        .collect();
+
+    writeln!(out)?;
+    for (name, command) in new {
+        match diff.sigil(name) {
+            Some('+') => writeln!(out, "{}", format!("    + {} : {}", name, command).green())?,
+            Some(_) => {
+                writeln!(out, "{}", format!("    ~ {} : {}", name, command).yellow())?;
+                let was = format!("      was: {}", old_commands[name.as_str()]);
+                writeln!(out, "{}", was.dimmed())?;
+            }
+            None => {}
+        }
+    }
+    for (name, command) in &diff.removed {
+        writeln!(out, "{}", format!("    - {} : {}", name, command).red())?;
+    }
+    if diff == ScriptDiff::default() {
+        writeln!(out, "   {}", "No script changes".dimmed())?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+// Scripts as last listed, keyed by absolute package.json path
+type Snapshots = BTreeMap<PathBuf, BTreeMap<String, String>>;
+
+/// Where the scripts seen by the last `--highlight-changed` listing are kept
+pub fn snapshot_file() -> Option<PathBuf> {
+    history::state_dir().map(|dir| dir.join("last-listing.json"))
+}
+
+/// The scripts `package` had when last listed, if it was listed before
+pub fn previous(snapshot_file: &Path, package: &Path) -> Result<Option<Vec<(String, String)>>> {
+    Ok(read(snapshot_file)?
+        .remove(package)
+        .map(|scripts| scripts.into_iter().collect()))
+}
+
+/// Remember `scripts` as the last listing of `package`
+pub fn save(snapshot_file: &Path, package: &Path, scripts: &[(String, String)]) -> Result<()> {
+    let mut snapshots = read(snapshot_file)?;
+    snapshots.insert(package.to_path_buf(), scripts.iter().cloned().collect());
+
+    if let Some(dir) = snapshot_file.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    fs::write(snapshot_file, serde_json::to_string_pretty(&snapshots)?)
+        .with_context(|| format!("Failed to write {}", snapshot_file.display()))
+}
+
+// A missing or unreadable snapshot just means nothing to compare against
+fn read(snapshot_file: &Path) -> Result<Snapshots> {
+    match fs::read_to_string(snapshot_file) {
+        Ok(content) => Ok(serde_json::from_str(&content).unwrap_or_default()),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Snapshots::new()),
+        Err(e) => Err(e).with_context(|| format!("Failed to read {}", snapshot_file.display())),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripts(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, command)| (name.to_string(), command.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_diff_with_previous() {
+        let old = scripts(&[
+            ("build", "tsc"),
+            ("lint", "eslint ."),
+            ("e2e", "cypress run"),
+        ]);
+        let new = scripts(&[("build", "tsc -b"), ("lint", "eslint ."), ("test", "jest")]);
+
+        let diff = diff_with_previous(&new, &old);
+        assert_eq!(diff.added, HashSet::from(["test".to_string()]));
+        assert_eq!(diff.changed, HashSet::from(["build".to_string()]));
+        assert_eq!(diff.removed, scripts(&[("e2e", "cypress run")]));
+        assert_eq!(diff.sigil("test"), Some('+'));
+        assert_eq!(diff.sigil("build"), Some('~'));
+        assert_eq!(diff.sigil("lint"), None);
+
+        assert_eq!(diff_with_previous(&new, &new), ScriptDiff::default());
+    }
+
+    #[test]
+    fn test_command_hash() {
+        let hash = command_hash("tsc");
+        assert_eq!(hash, "5efcd711");
+        assert_eq!(hash, command_hash("tsc"));
+        assert_ne!(hash, command_hash("tsc "));
+        assert_ne!(command_hash("tsc -b"), command_hash("tsc -p"));
+    }
+
+    #[test]
+    fn test_compute_set_diff() {
+        let a = scripts(&[("build", "tsc"), ("lint", "eslint ."), ("test", "jest")]);
+        let b = scripts(&[("build", "tsc -b"), ("lint", "eslint ."), ("dev", "vite")]);
+
+        let diff = compute_set_diff(&[a.clone(), b.clone()]);
+        let some = |command: &str| Some(command.to_string());
+        assert_eq!(
+            diff.differing,
+            [
+                ("build".to_string(), vec![some("tsc"), some("tsc -b")]),
+                ("dev".to_string(), vec![None, some("vite")]),
+                ("test".to_string(), vec![some("jest"), None]),
+            ]
+        );
+        assert_eq!(diff.identical, ["lint"]);
+
+        let same = compute_set_diff(&[a.clone(), a.clone(), a]);
+        assert!(same.differing.is_empty());
+        assert_eq!(same.identical, ["build", "lint", "test"]);
+    }
+
+    #[test]
+    fn test_print_set_diff() {
+        control::set_override(false);
+        let diff = compute_set_diff(&[
+            scripts(&[("build", "tsc"), ("lint", "eslint .")]),
+            scripts(&[("lint", "eslint .")]),
+        ]);
+        let paths = [
+            PathBuf::from("a/package.json"),
+            PathBuf::from("b/package.json"),
+        ];
+
+        let mut out = Vec::new();
+        print_set_diff(&mut out, &paths, &diff).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n    - build\n\
+             \x20     a/package.json: tsc\n\
+             \x20     b/package.json: (missing)\n\n\
+             \x20  1 script(s) are the same in every file\n\n"
+        );
+    }
+
+    #[test]
+    fn test_print_render_diff() {
+        control::set_override(false);
+        let old = scripts(&[
+            ("build", "tsc"),
+            ("e2e", "cypress run"),
+            ("lint", "eslint ."),
+        ]);
+        let new = scripts(&[("build", "tsc -b"), ("lint", "eslint ."), ("test", "jest")]);
+
+        let mut out = Vec::new();
+        print_render_diff(&mut out, &new, &old).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n    ~ build : tsc -b\n\
+             \x20     was: tsc\n\
+             \x20   + test : jest\n\
+             \x20   - e2e : cypress run\n\n"
+        );
+
+        let mut out = Vec::new();
+        print_render_diff(&mut out, &new, &new).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   No script changes\n\n"
+        );
+    }
+
+    #[test]
+    fn test_snapshots() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("state/last-listing.json");
+        let app = Path::new("/work/app/package.json");
+        let other = Path::new("/work/other/package.json");
+
+        assert_eq!(previous(&file, app).unwrap(), None);
+        save(&file, app, &scripts(&[("build", "tsc")])).unwrap();
+        save(&file, other, &scripts(&[("lint", "eslint .")])).unwrap();
+        assert_eq!(
+            previous(&file, app).unwrap(),
+            Some(scripts(&[("build", "tsc")]))
+        );
+    }
+}
@@ -0,0 +1,415 @@
+use std::env;
+use std::fs;
+use std::io::Read;
+use std::path::{Path, PathBuf};
+
+use crate::shell;
+
+/// Warn about scripts that run a local file (`./deploy.sh`) which lacks a
+/// shebang or the executable bit, a common cause of "permission denied" at
+/// run time. Files run through an interpreter (`bash deploy.sh`) are fine
+/// either way and aren't checked, as are system programs given by absolute
+/// path (`/usr/bin/env`) and compiled binaries, which need no shebang.
+pub fn check_shebangs(scripts: &[(String, String)], package_dir: &Path) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (name, command) in scripts {
+        for program in shell::programs(command) {
+            if !program.contains('/') || Path::new(&program).is_absolute() {
+                continue;
+            }
+            let path = package_dir.join(&program);
+            if !path.is_file() {
+                continue;
+            }
+
+            if !has_shebang(&path) && !is_binary(&path) {
+                warnings.push(format!(
+                    "script `{}`: {} has no shebang line",
+                    name, program
+                ));
+            }
+            if !is_executable(&path) {
+                warnings.push(format!("script `{}`: {} is not executable", name, program));
+            }
+        }
+    }
+    warnings
+}
+
+// Builtins and keywords that won't be found on PATH but run fine
+const SHELL_BUILTINS: &[&str] = &[
+    ".", ":", "[", "alias", "break", "case", "cd", "command", "continue", "do", "done", "echo",
+    "elif", "else", "esac", "eval", "exec", "exit", "export", "false", "fi", "for", "if", "printf",
+    "pwd", "read", "return", "set", "shift", "source", "test", "then", "trap", "true", "type",
+    "ulimit", "umask", "unset", "wait", "while",
+];
+
+// What cmd.exe falls back to when PATHEXT isn't set
+const DEFAULT_PATHEXT: &str = ".COM;.EXE;.BAT;.CMD";
+
+/// Warn about scripts whose programs can't be found. Like npm, the
+/// package's `node_modules/.bin` is searched before PATH.
+pub fn check_runners(scripts: &[(String, String)], package_dir: &Path) -> Vec<String> {
+    let bin_dir = package_dir.join("node_modules").join(".bin");
+    let mut warnings = Vec::new();
+    for (name, command) in scripts {
+        for program in shell::programs(command) {
+            if program.contains(['/', '\\', '$', '`']) || SHELL_BUILTINS.contains(&program.as_str())
+            {
+                continue;
+            }
+            let in_bin_dir = find_executable(&program, [bin_dir.clone()], pathext().as_deref());
+            if in_bin_dir.is_none() && find_executable_in_path(&program).is_none() {
+                warnings.push(format!(
+                    "script `{}`: `{}` not found in node_modules/.bin or PATH",
+                    name, program
+                ));
+            }
+        }
+    }
+    warnings
+}
+
+// Characters a shell treats specially, so `npm run <name>` needs quoting
+const SHELL_METACHARACTERS: &str = "$`\\|&;<>()*?![]{}#~";
+
+/// Flag script names that can't be passed to `npm run` as a plain word
+pub fn check_names(scripts: &[(String, String)]) -> Vec<String> {
+    scripts
+        .iter()
+        .filter_map(|(name, _)| {
+            let reason = if name.is_empty() {
+                "is empty".to_string()
+            } else if name.contains(char::is_whitespace) {
+                "contains whitespace".to_string()
+            } else if let Some(quote) = name.chars().find(|&c| c == '\'' || c == '"') {
+                format!("contains a quote ({})", quote)
+            } else if let Some(c) = name.chars().find(|&c| SHELL_METACHARACTERS.contains(c)) {
+                format!("contains the shell metacharacter `{}`", c)
+            } else if name.starts_with('-') {
+                "starts with `-` and would be read as an option".to_string()
+            } else {
+                return None;
+            };
+            Some(format!("script name {:?} {}", name, reason))
+        })
+        .collect()
+}
+
+// Unix programs Windows cmd doesn't have, with a cross-platform package to
+// use instead
+const POSIX_PROGRAMS: &[(&str, &str)] = &[
+    ("rm", "rimraf or shx rm"),
+    ("cp", "cpy-cli or shx cp"),
+    ("mv", "shx mv"),
+    ("mkdir", "mkdirp or shx mkdir"),
+    ("ln", "shx ln"),
+    ("touch", "shx touch"),
+    ("chmod", "shx chmod"),
+    ("cat", "shx cat"),
+    ("ls", "shx ls"),
+    ("grep", "shx grep"),
+    ("sed", "shx sed"),
+    ("which", "shx which"),
+];
+
+/// Warn about scripts that probably only run in a POSIX shell: Unix-only
+/// programs, `export` and `NAME=value` prefixes, and `$(...)` substitution.
+/// npm runs scripts with cmd.exe on Windows, which has none of those.
+pub fn check_portability(scripts: &[(String, String)]) -> Vec<String> {
+    let mut warnings = Vec::new();
+    for (name, command) in scripts {
+        let mut reasons = Vec::new();
+        for words in shell::simple_commands(command) {
+            let prefixed = words
+                .iter()
+                .take_while(|word| shell::is_assignment(word))
+                .count();
+            match words.get(prefixed).map(String::as_str) {
+                Some("export") => reasons.push("`export` (try cross-env)".to_string()),
+                Some(program) => {
+                    if prefixed > 0 {
+                        reasons.push("a `NAME=value` prefix (try cross-env)".to_string());
+                    }
+                    if let Some((posix, instead)) =
+                        POSIX_PROGRAMS.iter().find(|(p, _)| *p == program)
+                    {
+                        reasons.push(format!("`{}` (try {})", posix, instead));
+                    }
+                }
+                None => {}
+            }
+        }
+        if command.contains("$(") || command.contains('`') {
+            reasons.push("`$(...)` command substitution".to_string());
+        }
+        // Once each, however many times a script uses it
+        let mut seen = Vec::new();
+        reasons.retain(|reason| {
+            let first = !seen.contains(reason);
+            seen.push(reason.clone());
+            first
+        });
+
+        if !reasons.is_empty() {
+            warnings.push(format!(
+                "script `{}` may not run on Windows: uses {}",
+                name,
+                reasons.join(", ")
+            ));
+        }
+    }
+    warnings
+}
+
+/// The names in `required` (`--require`) that `scripts` doesn't define, in
+/// the order given
+pub fn missing_scripts<'a>(scripts: &[(String, String)], required: &'a [String]) -> Vec<&'a str> {
+    required
+        .iter()
+        .filter(|name| !scripts.iter().any(|(defined, _)| defined == *name))
+        .map(String::as_str)
+        .collect()
+}
+
+/// Look `name` up in PATH the way the platform's shell would: on Windows
+/// each PATHEXT extension is tried (`node` finds `node.exe`), elsewhere the
+/// file has to be executable
+pub fn find_executable_in_path(name: &str) -> Option<PathBuf> {
+    let path = env::var_os("PATH")?;
+    find_executable(name, env::split_paths(&path), pathext().as_deref())
+}
+
+fn pathext() -> Option<String> {
+    cfg!(windows).then(|| env::var("PATHEXT").unwrap_or_else(|_| DEFAULT_PATHEXT.to_string()))
+}
+
+fn find_executable(
+    name: &str,
+    dirs: impl IntoIterator<Item = PathBuf>,
+    pathext: Option<&str>,
+) -> Option<PathBuf> {
+    dirs.into_iter().find_map(|dir| match pathext {
+        Some(pathext) => {
+            let extensions: Vec<&str> = pathext.split(';').filter(|e| !e.is_empty()).collect();
+            let has_extension = extensions
+                .iter()
+                .any(|ext| name.to_lowercase().ends_with(&ext.to_lowercase()));
+            let plain = has_extension.then(|| dir.join(name));
+            plain
+                .into_iter()
+                .chain(
+                    extensions
+                        .iter()
+                        .map(|ext| dir.join(format!("{}{}", name, ext))),
+                )
+                .find(|candidate| candidate.is_file())
+        }
+        None => {
+            let candidate = dir.join(name);
+            (candidate.is_file() && is_executable(&candidate)).then_some(candidate)
+        }
+    })
+}
+
+fn has_shebang(path: &Path) -> bool {
+    let mut magic = [0; 2];
+    fs::File::open(path)
+        .and_then(|mut f| f.read_exact(&mut magic))
+        .is_ok_and(|_| &magic == b"#!")
+}
+
+// An ELF, Mach-O or PE executable, going by its first bytes
+fn is_binary(path: &Path) -> bool {
+    const MAGIC: &[&[u8]] = &[
+        b"\x7fELF",
+        b"\xcf\xfa\xed\xfe",
+        b"\xce\xfa\xed\xfe",
+        b"\xca\xfe\xba\xbe",
+        b"MZ",
+    ];
+    let mut start = [0; 4];
+    let Ok(read) = fs::File::open(path).and_then(|mut f| f.read(&mut start)) else {
+        return false;
+    };
+    MAGIC.iter().any(|magic| start[..read].starts_with(magic))
+}
+
+#[cfg(unix)]
+fn is_executable(path: &Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    fs::metadata(path).is_ok_and(|m| m.permissions().mode() & 0o111 != 0)
+}
+
+// Windows has no executable bit; runnability comes from the file extension
+#[cfg(not(unix))]
+fn is_executable(_path: &Path) -> bool {
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_executable_with_pathext() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("node.EXE"), "").unwrap();
+        fs::write(dir.path().join("eslint.cmd"), "").unwrap();
+        let dirs = || [PathBuf::from("/nonexistent"), dir.path().to_path_buf()];
+        let pathext = Some(".COM;.EXE;.BAT;.CMD");
+
+        assert_eq!(
+            find_executable("node", dirs(), pathext),
+            Some(dir.path().join("node.EXE"))
+        );
+        assert_eq!(
+            find_executable("eslint", dirs(), Some(".COM;.EXE;.BAT;.cmd")),
+            Some(dir.path().join("eslint.cmd"))
+        );
+        assert_eq!(
+            find_executable("node.EXE", dirs(), pathext),
+            Some(dir.path().join("node.EXE"))
+        );
+        assert_eq!(find_executable("jest", dirs(), pathext), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_find_executable_without_pathext() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let write = |file: &str, mode: u32| {
+            let path = dir.path().join(file);
+            fs::write(&path, "").unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        };
+        write("tsc", 0o755);
+        write("README", 0o644);
+        let dirs = || [dir.path().to_path_buf()];
+
+        assert_eq!(
+            find_executable("tsc", dirs(), None),
+            Some(dir.path().join("tsc"))
+        );
+        assert_eq!(find_executable("README", dirs(), None), None);
+        assert_eq!(find_executable("node", dirs(), None), None);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_runners() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let bin_dir = dir.path().join("node_modules/.bin");
+        fs::create_dir_all(&bin_dir).unwrap();
+        fs::write(bin_dir.join("tsc"), "").unwrap();
+        fs::set_permissions(bin_dir.join("tsc"), fs::Permissions::from_mode(0o755)).unwrap();
+
+        let scripts = [
+            script("build", "NODE_ENV=production tsc && echo done"),
+            script("deploy", "./deploy.sh && $DEPLOYER"),
+            script("lint", "sl-no-such-linter ."),
+        ];
+        assert_eq!(
+            check_runners(&scripts, dir.path()),
+            ["script `lint`: `sl-no-such-linter` not found in node_modules/.bin or PATH"]
+        );
+    }
+
+    #[test]
+    fn test_check_names() {
+        let scripts = [
+            script("build:prod", ""),
+            script("test @unit/x+1", ""),
+            script("say'hi'", ""),
+            script("deploy;rm", ""),
+            script("--help", ""),
+            script("", ""),
+        ];
+        assert_eq!(
+            check_names(&scripts),
+            [
+                r#"script name "test @unit/x+1" contains whitespace"#,
+                r#"script name "say'hi'" contains a quote (')"#,
+                r#"script name "deploy;rm" contains the shell metacharacter `;`"#,
+                r#"script name "--help" starts with `-` and would be read as an option"#,
+                r#"script name "" is empty"#,
+            ]
+        );
+    }
+
+    #[test]
+    fn test_check_portability() {
+        let scripts = [
+            script("clean", "rm -rf dist && mkdir dist"),
+            script("build", "NODE_ENV=production webpack"),
+            script("env", "export CI=1 && jest"),
+            script("stamp", "echo $(git rev-parse HEAD) > VERSION"),
+            script("portable", "rimraf dist && cross-env NODE_ENV=test jest"),
+        ];
+        assert_eq!(
+            check_portability(&scripts),
+            [
+                "script `clean` may not run on Windows: uses `rm` (try rimraf or shx rm), \
+                 `mkdir` (try mkdirp or shx mkdir)",
+                "script `build` may not run on Windows: uses a `NAME=value` prefix (try cross-env)",
+                "script `env` may not run on Windows: uses `export` (try cross-env)",
+                "script `stamp` may not run on Windows: uses `$(...)` command substitution",
+            ]
+        );
+    }
+
+    fn script(name: &str, command: &str) -> (String, String) {
+        (name.to_string(), command.to_string())
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_check_shebangs() {
+        use std::os::unix::fs::PermissionsExt;
+
+        let dir = tempfile::tempdir().unwrap();
+        let write = |file: &str, content: &str, mode: u32| {
+            let path = dir.path().join(file);
+            fs::write(&path, content).unwrap();
+            fs::set_permissions(&path, fs::Permissions::from_mode(mode)).unwrap();
+        };
+        write("ok.sh", "#!/bin/sh\necho ok\n", 0o755);
+        write("no-shebang.sh", "echo hi\n", 0o755);
+        write("no-exec.sh", "#!/bin/sh\n", 0o644);
+        write("tool", "\x7fELF\x02\x01\x01", 0o755);
+
+        let scripts = [
+            script("ok", "./ok.sh"),
+            script("shebang", "CI=1 ./no-shebang.sh --flag"),
+            script("exec", "npm test && ./no-exec.sh"),
+            script("interpreted", "bash ./no-exec.sh"),
+            script("missing", "./missing.sh"),
+            script("binary", "./tool --help"),
+            script("system", "/bin/sh ./ok.sh && /usr/bin/env node x.js"),
+            // An absolute path counts as a system program, wherever it points
+            script(
+                "absolute",
+                &dir.path().join("no-shebang.sh").to_string_lossy(),
+            ),
+        ];
+        assert_eq!(
+            check_shebangs(&scripts, dir.path()),
+            [
+                "script `shebang`: ./no-shebang.sh has no shebang line",
+                "script `exec`: ./no-exec.sh is not executable",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_missing_scripts() {
+        let scripts = [script("build", "tsc"), script("test", "jest")];
+        let required = ["lint", "test", "build", "typecheck"].map(String::from);
+        assert_eq!(missing_scripts(&scripts, &required), ["lint", "typecheck"]);
+        assert!(missing_scripts(&scripts, &required[1..3]).is_empty());
+    }
+}
@@ -0,0 +1,140 @@
+use anyhow::{Context, Result};
+
+/// Somewhere `--copy` can put text; the system clipboard in practice, a
+/// recorder in tests
+pub trait Clipboard {
+    fn set_text(&mut self, text: &str) -> Result<()>;
+}
+
+pub struct SystemClipboard(arboard::Clipboard);
+
+impl SystemClipboard {
+    /// Fails when there's no clipboard to talk to, e.g. over SSH or in CI
+    /// without a display server
+    pub fn new() -> Result<Self> {
+        Ok(SystemClipboard(arboard::Clipboard::new()?))
+    }
+}
+
+/// What sl is started with to become the process that holds on to copied
+/// text, see `serve_from_stdin`
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+pub const HELPER_ARG: &str = "__clipboard-helper";
+
+#[cfg(not(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+)))]
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        Ok(self.0.set_text(text)?)
+    }
+}
+
+// On X11 and Wayland the copied text lives in the process that copied it, so
+// once set (which shows whether there's a clipboard at all) it's handed to a
+// helper that outlives sl and serves it until something else is copied
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+impl Clipboard for SystemClipboard {
+    fn set_text(&mut self, text: &str) -> Result<()> {
+        use std::io::Write;
+        use std::os::unix::process::CommandExt;
+        use std::process::{Command, Stdio};
+
+        self.0.set_text(text)?;
+        let exe = std::env::current_exe().context("Failed to find the sl executable")?;
+        let mut helper = Command::new(exe)
+            .arg(HELPER_ARG)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .current_dir("/")
+            // Out of the terminal's way, so Ctrl-C after sl is done leaves it be
+            .process_group(0)
+            .spawn()
+            .context("Failed to start the clipboard helper")?;
+        let mut stdin = helper.stdin.take().expect("stdin is piped");
+        stdin
+            .write_all(text.as_bytes())
+            .context("Failed to hand the text to the clipboard helper")?;
+        Ok(())
+    }
+}
+
+/// As the helper: put what's on stdin on the clipboard, and keep serving it
+/// until it's replaced
+#[cfg(all(
+    unix,
+    not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+))]
+pub fn serve_from_stdin() -> Result<()> {
+    use arboard::SetExtLinux;
+    use std::io::Read;
+
+    let mut text = String::new();
+    std::io::stdin().read_to_string(&mut text)?;
+    arboard::Clipboard::new()?.set().wait().text(text)?;
+    Ok(())
+}
+
+/// Copy rendered output to `clipboard`, minus the color codes that only make
+/// sense in a terminal
+pub fn copy_output(clipboard: &mut impl Clipboard, output: &[u8]) -> Result<()> {
+    let text = String::from_utf8_lossy(output);
+    clipboard.set_text(&strip_ansi(&text))
+}
+
+/// Remove ANSI escape sequences (`ESC [ ... <letter>`) from `text`
+pub fn strip_ansi(text: &str) -> String {
+    let mut stripped = String::with_capacity(text.len());
+    let mut chars = text.chars();
+    while let Some(c) = chars.next() {
+        if c == '\x1b' {
+            if chars.next() == Some('[') {
+                for c in chars.by_ref() {
+                    if c.is_ascii_alphabetic() {
+                        break;
+                    }
+                }
+            }
+            continue;
+        }
+        stripped.push(c);
+    }
+    stripped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Default)]
+    struct MockClipboard(Option<String>);
+
+    impl Clipboard for MockClipboard {
+        fn set_text(&mut self, text: &str) -> Result<()> {
+            self.0 = Some(text.to_string());
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_copy_output() {
+        let mut clipboard = MockClipboard::default();
+        copy_output(
+            &mut clipboard,
+            b"\n   \x1b[32mapp\x1b[0m\n\n    - build : tsc\n",
+        )
+        .unwrap();
+        assert_eq!(
+            clipboard.0.as_deref(),
+            Some("\n   app\n\n    - build : tsc\n")
+        );
+    }
+}
@@ -0,0 +1,83 @@
+// The `colored` crate, or without the default `color` feature stand-ins with
+// the same names that leave the text as it is, for builds that want no ANSI
+// codes and one dependency fewer
+
+#[cfg(feature = "color")]
+pub use colored::{control, ColoredString, Colorize};
+
+#[cfg(not(feature = "color"))]
+pub use plain::{control, ColoredString, Colorize};
+
+#[cfg(not(feature = "color"))]
+mod plain {
+    use std::fmt;
+
+    /// Text that was meant to be colored
+    #[derive(Clone, Debug, Default, PartialEq, Eq)]
+    pub struct ColoredString(String);
+
+    impl fmt::Display for ColoredString {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            // Through `pad` so `{:<10}` and friends still line things up
+            f.pad(&self.0)
+        }
+    }
+
+    /// The styles the code uses, each returning the text unchanged
+    pub trait Colorize: Sized {
+        fn into_plain(self) -> ColoredString;
+
+        fn normal(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn bold(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn dimmed(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn red(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn green(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn yellow(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn cyan(self) -> ColoredString {
+            self.into_plain()
+        }
+        fn truecolor(self, _r: u8, _g: u8, _b: u8) -> ColoredString {
+            self.into_plain()
+        }
+    }
+
+    impl Colorize for &str {
+        fn into_plain(self) -> ColoredString {
+            ColoredString(self.to_string())
+        }
+    }
+
+    impl Colorize for ColoredString {
+        fn into_plain(self) -> ColoredString {
+            self
+        }
+    }
+
+    pub mod control {
+        /// Whether to color, which without `colored` is never
+        pub struct ShouldColorize;
+
+        impl ShouldColorize {
+            pub fn should_colorize(&self) -> bool {
+                false
+            }
+        }
+
+        pub static SHOULD_COLORIZE: ShouldColorize = ShouldColorize;
+
+        /// `--color always` has nothing to turn on
+        pub fn set_override(_enabled: bool) {}
+    }
+}
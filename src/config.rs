@@ -0,0 +1,90 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::fs;
+use std::path::Path;
+
+use crate::destructive;
+use crate::summary::SummaryRule;
+
+/// Settings from the optional `.scriptrc.json` next to package.json
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields, rename_all = "camelCase")]
+pub struct Config {
+    /// Extra `--summarize` rules, tried before the built-in ones
+    pub summaries: Vec<SummaryRule>,
+    /// package.json key to read script descriptions from, when
+    /// `--script-info-key` isn't given
+    pub script_info_key: Option<String>,
+    /// More scripts for `--run` to ask about before running
+    pub destructive: destructive::Rules,
+    /// Script names for `--sort-by priority` to put first, in this order
+    pub order: Vec<String>,
+}
+
+pub const FILE_NAME: &str = ".scriptrc.json";
+
+impl Config {
+    /// The config in `package_dir`, or the defaults if there isn't one
+    pub fn load(package_dir: &Path) -> Result<Self> {
+        let path = package_dir.join(FILE_NAME);
+        let content = match fs::read_to_string(&path) {
+            Ok(content) => content,
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Config::default()),
+            Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+        };
+        serde_json::from_str(&content)
+            .with_context(|| format!("Failed to parse {}", path.display()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_load() {
+        let dir = tempfile::tempdir().unwrap();
+        assert!(Config::load(dir.path()).unwrap().summaries.is_empty());
+
+        fs::write(
+            dir.path().join(FILE_NAME),
+            r#"{ "summaries": [{ "pattern": "^turbo run", "summary": "Run a Turborepo pipeline" }] }"#,
+        )
+        .unwrap();
+        assert_eq!(Config::load(dir.path()).unwrap().summaries.len(), 1);
+
+        fs::write(
+            dir.path().join(FILE_NAME),
+            r#"{ "summaries": [{ "pattern": "(" , "summary": "" }] }"#,
+        )
+        .unwrap();
+        assert!(Config::load(dir.path()).is_err());
+
+        fs::write(
+            dir.path().join(FILE_NAME),
+            r#"{ "scriptInfoKey": "scriptsComments" }"#,
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.script_info_key.as_deref(), Some("scriptsComments"));
+
+        fs::write(
+            dir.path().join(FILE_NAME),
+            r#"{ "destructive": { "names": ["release"], "commands": ["^terraform destroy"] } }"#,
+        )
+        .unwrap();
+        let config = Config::load(dir.path()).unwrap();
+        assert_eq!(config.destructive.names, ["release"]);
+        assert_eq!(config.destructive.commands.len(), 1);
+
+        fs::write(
+            dir.path().join(FILE_NAME),
+            r#"{ "order": ["dev", "build", "test"] }"#,
+        )
+        .unwrap();
+        assert_eq!(
+            Config::load(dir.path()).unwrap().order,
+            ["dev", "build", "test"]
+        );
+    }
+}
@@ -0,0 +1,133 @@
+use regex::Regex;
+use serde::Deserialize;
+use std::fmt;
+use std::sync::OnceLock;
+
+// Script name parts (`clean` in `clean:dist`, `reset` in `db-reset`)
+const NAMES: &[&str] = &[
+    "clean", "reset", "prune", "deploy", "destroy", "purge", "wipe", "nuke", "drop",
+];
+
+// Deleting files, throwing away git work, dropping databases
+const COMMANDS: &str = concat!(
+    r"(?i)\brm\s+-\w*[rf]\w*|\brimraf\b",
+    r"|\bgit\s+reset\s+--hard\b|\bgit\s+clean\s+-\w*f|\bgit\s+push\b.*\s(--force\b|-f\b)",
+    r"|\bdrop\s+(database|table|schema)\b",
+    r"|\bprune\b|\bdeploy\b",
+);
+
+/// Extra names and command patterns to treat as destructive, from the
+/// `destructive` key of .scriptrc.json
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Rules {
+    /// Matched against the parts of a script name, like the built-in ones
+    pub names: Vec<String>,
+    /// Regexes matched against the command
+    pub commands: Vec<Pattern>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(try_from = "String")]
+pub struct Pattern(Regex);
+
+impl TryFrom<String> for Pattern {
+    type Error = regex::Error;
+
+    fn try_from(pattern: String) -> Result<Self, Self::Error> {
+        Ok(Pattern(Regex::new(&pattern)?))
+    }
+}
+
+/// What makes a script look destructive
+#[derive(Debug, PartialEq, Eq)]
+pub enum Match {
+    /// A part of its name
+    Name(String),
+    /// The bit of its command matching a pattern
+    Command(String),
+}
+
+impl fmt::Display for Match {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Match::Name(part) => write!(f, "its name has `{}`", part),
+            Match::Command(found) => write!(f, "it runs `{}`", found),
+        }
+    }
+}
+
+/// Why the script looks destructive, if it does
+pub fn destructive_match(name: &str, command: &str, rules: &Rules) -> Option<Match> {
+    let named = name
+        .split([':', '-', '_', '.'])
+        .find(|part| NAMES.contains(part) || rules.names.iter().any(|n| n == part));
+    if let Some(part) = named {
+        return Some(Match::Name(part.to_string()));
+    }
+
+    static BUILT_IN: OnceLock<Regex> = OnceLock::new();
+    let built_in = BUILT_IN.get_or_init(|| Regex::new(COMMANDS).unwrap());
+    std::iter::once(built_in)
+        .chain(rules.commands.iter().map(|Pattern(regex)| regex))
+        .find_map(|regex| regex.find(command))
+        .map(|found| Match::Command(found.as_str().trim().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use Match::{Command, Name};
+
+    #[test]
+    fn test_built_in() {
+        let rules = Rules::default();
+        let check = |name, command| destructive_match(name, command, &rules);
+
+        assert_eq!(
+            check("clean:dist", "tsc --build --clean"),
+            Some(Name("clean".into()))
+        );
+        assert_eq!(
+            check("db-reset", "prisma migrate"),
+            Some(Name("reset".into()))
+        );
+        assert_eq!(check("tidy", "rm -rf dist"), Some(Command("rm -rf".into())));
+        assert_eq!(
+            check("undo", "git reset --hard HEAD~1"),
+            Some(Command("git reset --hard".into()))
+        );
+        assert_eq!(
+            check("sync", "git push origin main -f"),
+            Some(Command("git push origin main -f".into()))
+        );
+        assert_eq!(
+            check("db", "psql -c 'DROP TABLE users'"),
+            Some(Command("DROP TABLE".into()))
+        );
+        assert_eq!(check("build", "tsc && rm dist/.tsbuildinfo"), None);
+        assert_eq!(check("cleanup-docs", "typedoc"), None);
+        assert_eq!(check("push", "git push origin main"), None);
+    }
+
+    #[test]
+    fn test_configured() {
+        let rules: Rules = serde_json::from_str(
+            r#"{ "names": ["release"], "commands": ["^terraform (apply|destroy)"] }"#,
+        )
+        .unwrap();
+        let check = |name, command| destructive_match(name, command, &rules);
+
+        assert_eq!(
+            check("release:major", "np major"),
+            Some(Name("release".into()))
+        );
+        assert_eq!(
+            check("infra", "terraform apply"),
+            Some(Command("terraform apply".into()))
+        );
+        assert_eq!(check("plan", "terraform plan"), None);
+        assert_eq!(Name("release".into()).to_string(), "its name has `release`");
+        assert!(serde_json::from_str::<Rules>(r#"{ "commands": ["("] }"#).is_err());
+    }
+}
@@ -0,0 +1,84 @@
+use encoding_rs::mem;
+
+/// Character encoding for `--output-encoding`
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum OutputEncoding {
+    #[value(name = "utf-8")]
+    Utf8,
+    /// Little-endian UTF-16, with a byte order mark
+    #[value(name = "utf-16-le")]
+    Utf16Le,
+    /// Big-endian UTF-16, with a byte order mark
+    #[value(name = "utf-16-be")]
+    Utf16Be,
+    /// ISO-8859-1; characters it doesn't have become `?`
+    #[value(name = "latin-1")]
+    Latin1,
+}
+
+/// `text` in `encoding`, and how many characters had to be replaced because
+/// the encoding can't represent them
+pub fn encode(text: &str, encoding: OutputEncoding) -> (Vec<u8>, usize) {
+    match encoding {
+        OutputEncoding::Utf8 => (text.as_bytes().to_vec(), 0),
+        OutputEncoding::Utf16Le => (utf16(text, [0xFF, 0xFE], u16::to_le_bytes), 0),
+        OutputEncoding::Utf16Be => (utf16(text, [0xFE, 0xFF], u16::to_be_bytes), 0),
+        OutputEncoding::Latin1 if mem::is_str_latin1(text) => {
+            (mem::encode_latin1_lossy(text).into_owned(), 0)
+        }
+        OutputEncoding::Latin1 => {
+            let mut replaced = 0;
+            let representable: String = text
+                .chars()
+                .map(|c| match c as u32 {
+                    0..=0xFF => c,
+                    _ => {
+                        replaced += 1;
+                        '?'
+                    }
+                })
+                .collect();
+            (
+                mem::encode_latin1_lossy(&representable).into_owned(),
+                replaced,
+            )
+        }
+    }
+}
+
+fn utf16(text: &str, bom: [u8; 2], to_bytes: fn(u16) -> [u8; 2]) -> Vec<u8> {
+    // Never more code units than UTF-8 bytes
+    let mut units = vec![0; text.len()];
+    let written = mem::convert_str_to_utf16(text, &mut units);
+    let mut bytes = bom.to_vec();
+    bytes.extend(units[..written].iter().flat_map(|&unit| to_bytes(unit)));
+    bytes
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_utf16_has_a_bom() {
+        let (le, replaced) = encode("sl é", OutputEncoding::Utf16Le);
+        assert_eq!(le, [0xFF, 0xFE, b's', 0, b'l', 0, b' ', 0, 0xE9, 0]);
+        assert_eq!(replaced, 0);
+
+        let (be, _) = encode("→", OutputEncoding::Utf16Be);
+        assert_eq!(be, [0xFE, 0xFF, 0x21, 0x92]);
+    }
+
+    #[test]
+    fn test_latin1_replaces_what_it_cant_represent() {
+        assert_eq!(
+            encode("café", OutputEncoding::Latin1),
+            (vec![b'c', b'a', b'f', 0xE9], 0)
+        );
+        assert_eq!(
+            encode("a → b ⚠️", OutputEncoding::Latin1),
+            (b"a ? b ??".to_vec(), 3)
+        );
+        assert_eq!(encode("ok", OutputEncoding::Utf8), (b"ok".to_vec(), 0));
+    }
+}
@@ -0,0 +1,217 @@
+use anyhow::{Context, Result};
+use semver::{Version, VersionReq};
+use std::fs;
+use std::path::Path;
+use std::process::Command;
+
+use crate::package_manager::{detect_lockfile, Lockfile, PackageManager};
+
+/// A Node.js version range and the file it was read from
+#[derive(Debug, PartialEq, Eq)]
+pub struct NodeRequirement {
+    pub range: String,
+    pub source: String,
+}
+
+/// Resolve the Node.js requirement for the package in `dir`.
+///
+/// package-lock.json records the root package's `engines` as resolved at
+/// install time, so it wins over package.json. yarn, pnpm and bun lockfiles
+/// don't store root engines, so those fall back to `package_range`.
+pub fn node_requirement(
+    dir: &Path,
+    package_range: Option<&str>,
+) -> Result<Option<NodeRequirement>> {
+    if let Some(lockfile) = detect_lockfile(dir) {
+        if let Some(range) = lockfile_node_range(&lockfile)? {
+            let source = lockfile.file_name();
+            return Ok(Some(NodeRequirement { range, source }));
+        }
+    }
+
+    Ok(package_range.map(|range| NodeRequirement {
+        range: range.to_string(),
+        source: "package.json".to_string(),
+    }))
+}
+
+fn lockfile_node_range(lockfile: &Lockfile) -> Result<Option<String>> {
+    if lockfile.manager != PackageManager::Npm {
+        return Ok(None);
+    }
+
+    let content = fs::read_to_string(&lockfile.path)
+        .with_context(|| format!("Failed to read {}", lockfile.path.display()))?;
+    let lock: serde_json::Value = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {} as JSON", lockfile.path.display()))?;
+
+    Ok(lock
+        .pointer("/packages//engines/node")
+        .and_then(|v| v.as_str())
+        .map(str::to_string))
+}
+
+/// Version of the `node` binary in PATH, if there is one
+pub fn current_node_version() -> Option<Version> {
+    let output = Command::new("node").arg("--version").output().ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let version = String::from_utf8_lossy(&output.stdout);
+    Version::parse(version.trim().trim_start_matches('v')).ok()
+}
+
+/// Check the current `node` against `requirement`, returning a warning if it
+/// isn't satisfied
+pub fn check_node(requirement: &NodeRequirement) -> Option<String> {
+    check_version(requirement, current_node_version())
+}
+
+// A range that doesn't parse is the package's problem, so it's one more
+// warning rather than a reason to stop listing
+fn check_version(requirement: &NodeRequirement, version: Option<Version>) -> Option<String> {
+    let Some(version) = version else {
+        return Some(format!(
+            "node not found in PATH (engines.node \"{}\" in {})",
+            requirement.range, requirement.source
+        ));
+    };
+
+    match satisfies(&requirement.range, &version) {
+        Ok(true) => None,
+        Ok(false) => Some(format!(
+            "Node.js v{} does not satisfy engines.node \"{}\" in {}",
+            version, requirement.range, requirement.source
+        )),
+        Err(_) => Some(format!(
+            "invalid engines.node range \"{}\" in {}",
+            requirement.range, requirement.source
+        )),
+    }
+}
+
+/// Whether `version` satisfies an npm-style semver range such as
+/// `>=18.0.0`, `^16 || ^18` or `14.x - 16`
+pub fn satisfies(range: &str, version: &Version) -> Result<bool> {
+    for alternative in range.split("||") {
+        let req = to_version_req(alternative)
+            .with_context(|| format!("Invalid engines range \"{}\"", range))?;
+        if req.matches(version) {
+            return Ok(true);
+        }
+    }
+    Ok(false)
+}
+
+// npm separates comparators with spaces and treats a bare version as exact,
+// whereas the semver crate wants commas and treats a bare version as a caret
+fn to_version_req(range: &str) -> Result<VersionReq> {
+    let range = range.trim();
+    if range.is_empty() {
+        return Ok(VersionReq::STAR);
+    }
+
+    if let Some((low, high)) = range.split_once(" - ") {
+        return Ok(VersionReq::parse(&format!(
+            ">={}, <={}",
+            low.trim().trim_start_matches('v'),
+            high.trim().trim_start_matches('v')
+        ))?);
+    }
+
+    let mut comparators = Vec::new();
+    let mut pending_op = String::new();
+    for token in range.split_whitespace() {
+        if token.chars().all(|c| "<>=~^".contains(c)) {
+            pending_op = token.to_string();
+            continue;
+        }
+
+        let token = format!("{}{}", std::mem::take(&mut pending_op), token);
+        let version_start = token.find(|c: char| !"<>=~^".contains(c)).unwrap_or(0);
+        let (op, version) = token.split_at(version_start);
+        let version = version.trim_start_matches('v');
+        let op = if op.is_empty() && !version.starts_with(['*', 'x', 'X']) {
+            "="
+        } else {
+            op
+        };
+        comparators.push(format!("{}{}", op, version));
+    }
+
+    Ok(VersionReq::parse(&comparators.join(", "))?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn v(s: &str) -> Version {
+        Version::parse(s).unwrap()
+    }
+
+    #[test]
+    fn test_satisfies() {
+        assert!(satisfies(">=18.0.0", &v("20.11.0")).unwrap());
+        assert!(!satisfies(">=18.0.0", &v("16.20.2")).unwrap());
+        assert!(satisfies("^16 || ^18", &v("18.1.0")).unwrap());
+        assert!(!satisfies("^16 || ^18", &v("20.0.0")).unwrap());
+        assert!(satisfies(">= 16 < 20", &v("19.9.9")).unwrap());
+        assert!(satisfies("14.x - 16", &v("16.3.0")).unwrap());
+        assert!(satisfies("18", &v("18.4.0")).unwrap());
+        assert!(!satisfies("18.0.0", &v("18.4.0")).unwrap());
+        assert!(satisfies("*", &v("1.0.0")).unwrap());
+        assert!(satisfies("", &v("1.0.0")).unwrap());
+    }
+
+    #[test]
+    fn test_check_version() {
+        let requirement = |range: &str| NodeRequirement {
+            range: range.to_string(),
+            source: "package.json".to_string(),
+        };
+        assert_eq!(check_version(&requirement(">=18"), Some(v("20.0.0"))), None);
+        assert_eq!(
+            check_version(&requirement(">=18"), Some(v("16.0.0"))).unwrap(),
+            "Node.js v16.0.0 does not satisfy engines.node \">=18\" in package.json"
+        );
+        assert_eq!(
+            check_version(&requirement("lts/iron"), Some(v("20.0.0"))).unwrap(),
+            "invalid engines.node range \"lts/iron\" in package.json"
+        );
+        assert!(check_version(&requirement(">=18"), None)
+            .unwrap()
+            .starts_with("node not found in PATH"));
+    }
+
+    #[test]
+    fn test_node_requirement_from_package_lock() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(
+            dir.path().join("package-lock.json"),
+            r#"{
+                "lockfileVersion": 3,
+                "packages": {
+                    "": { "name": "app", "engines": { "node": ">=18.0.0" } }
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let requirement = node_requirement(dir.path(), Some(">=14")).unwrap().unwrap();
+        assert_eq!(requirement.range, ">=18.0.0");
+        assert_eq!(requirement.source, "package-lock.json");
+    }
+
+    #[test]
+    fn test_node_requirement_falls_back_to_package_json() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join("yarn.lock"), "").unwrap();
+
+        let requirement = node_requirement(dir.path(), Some(">=14")).unwrap().unwrap();
+        assert_eq!(requirement.range, ">=14");
+        assert_eq!(requirement.source, "package.json");
+
+        assert_eq!(node_requirement(dir.path(), None).unwrap(), None);
+    }
+}
@@ -0,0 +1,225 @@
+use anyhow::{Context, Result};
+use std::collections::{BTreeMap, HashMap};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use crate::color::*;
+use crate::shell;
+
+/// A `.env` file and the variables it defines
+pub struct EnvFile {
+    pub path: PathBuf,
+    pub vars: HashMap<String, String>,
+}
+
+impl EnvFile {
+    pub fn load(path: &Path) -> Result<Self> {
+        let vars = dotenvy::from_path_iter(path)
+            .and_then(|iter| iter.collect::<Result<HashMap<_, _>, _>>())
+            .with_context(|| format!("Failed to load {}", path.display()))?;
+        Ok(EnvFile {
+            path: path.to_path_buf(),
+            vars,
+        })
+    }
+}
+
+/// The variables of all `paths` together, a later file's value winning over
+/// an earlier one's, in name order
+pub fn merge_env_files(paths: &[PathBuf]) -> Result<Vec<(String, String)>> {
+    let mut vars = BTreeMap::new();
+    for path in paths {
+        vars.extend(EnvFile::load(path)?.vars);
+    }
+    Ok(vars.into_iter().collect())
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum Change<'a> {
+    Same(&'a str),
+    Removed(&'a str),
+    Added(&'a str),
+}
+
+/// Word-by-word diff of `old` against `new`, from their longest common
+/// subsequence of words
+pub fn diff_words<'a>(old: &'a str, new: &'a str) -> Vec<Change<'a>> {
+    let old: Vec<&str> = old.split_whitespace().collect();
+    let new: Vec<&str> = new.split_whitespace().collect();
+
+    // common[i][j]: length of the LCS of old[i..] and new[j..]
+    let mut common = vec![vec![0; new.len() + 1]; old.len() + 1];
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            common[i][j] = if old[i] == new[j] {
+                common[i + 1][j + 1] + 1
+            } else {
+                common[i + 1][j].max(common[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let (mut i, mut j) = (0, 0);
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            changes.push(Change::Same(old[i]));
+            i += 1;
+            j += 1;
+        } else if j == new.len() || (i < old.len() && common[i + 1][j] >= common[i][j + 1]) {
+            changes.push(Change::Removed(old[i]));
+            i += 1;
+        } else {
+            changes.push(Change::Added(new[j]));
+            j += 1;
+        }
+    }
+    changes
+}
+
+/// Each script rendered under both environments, for the scripts where the
+/// two differ; the words that change are colored red and green
+pub fn print_comparison(
+    out: &mut impl Write,
+    scripts: &[(String, String)],
+    left: &EnvFile,
+    right: &EnvFile,
+) -> io::Result<()> {
+    let left_name = left.path.display().to_string();
+    let right_name = right.path.display().to_string();
+    let width = left_name.chars().count().max(right_name.chars().count()) + 1;
+
+    writeln!(out)?;
+    let title = format!("{} → {}", left_name, right_name);
+    writeln!(out, "   {}", title.green())?;
+    writeln!(out)?;
+
+    let mut same = 0;
+    for (name, command) in scripts {
+        let old = shell::expand_env_vars(command, &left.vars);
+        let new = shell::expand_env_vars(command, &right.vars);
+        if old == new {
+            same += 1;
+            continue;
+        }
+
+        let changes = diff_words(&old, &new);
+        let words = |keep: fn(&Change) -> Option<ColoredString>| {
+            changes
+                .iter()
+                .filter_map(keep)
+                .map(|word| word.to_string())
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+        let old_words = words(|change| match change {
+            Change::Same(word) => Some(word.normal()),
+            Change::Removed(word) => Some(word.red()),
+            Change::Added(_) => None,
+        });
+        let new_words = words(|change| match change {
+            Change::Same(word) => Some(word.normal()),
+            Change::Added(word) => Some(word.green()),
+            Change::Removed(_) => None,
+        });
+
+        writeln!(out, "    - {}", name.truecolor(128, 128, 128))?;
+        let left_label = format!("{}:", left_name);
+        let right_label = format!("{}:", right_name);
+        writeln!(out, "      {:<width$} {}", left_label, old_words)?;
+        writeln!(out, "      {:<width$} {}", right_label, new_words)?;
+    }
+
+    writeln!(out)?;
+    if same > 0 {
+        let footer = format!("   {} script(s) render the same in both", same);
+        writeln!(out, "{}", footer.dimmed())?;
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_diff_words() {
+        assert_eq!(
+            diff_words(
+                "NODE_ENV=development webpack --watch",
+                "NODE_ENV=production webpack"
+            ),
+            [
+                Change::Removed("NODE_ENV=development"),
+                Change::Added("NODE_ENV=production"),
+                Change::Same("webpack"),
+                Change::Removed("--watch"),
+            ]
+        );
+        assert_eq!(diff_words("tsc", "tsc"), [Change::Same("tsc")]);
+    }
+
+    #[test]
+    fn test_merge_env_files_reach_the_child() {
+        let dir = tempfile::tempdir().unwrap();
+        let base = dir.path().join(".env");
+        let local = dir.path().join(".env.local");
+        fs::write(&base, "# shared\nGREETING=\"hello there\"\nPORT=3000\n").unwrap();
+        fs::write(&local, "PORT='8080' # mine\n").unwrap();
+
+        let vars = merge_env_files(&[base, local]).unwrap();
+        assert_eq!(
+            vars,
+            [
+                ("GREETING".to_string(), "hello there".to_string()),
+                ("PORT".to_string(), "8080".to_string()),
+            ]
+        );
+
+        let output = std::process::Command::new("sh")
+            .args(["-c", "printf '%s:%s' \"$GREETING\" \"$PORT\""])
+            .envs(vars)
+            .output()
+            .unwrap();
+        assert_eq!(
+            String::from_utf8(output.stdout).unwrap(),
+            "hello there:8080"
+        );
+    }
+
+    #[test]
+    fn test_compare_node_envs() {
+        control::set_override(false);
+        let dir = tempfile::tempdir().unwrap();
+        fs::write(dir.path().join(".env.dev"), "NODE_ENV=development\n").unwrap();
+        fs::write(
+            dir.path().join(".env.prod"),
+            "# deployed\nNODE_ENV=production\n",
+        )
+        .unwrap();
+        let dev = EnvFile::load(&dir.path().join(".env.dev")).unwrap();
+        let prod = EnvFile::load(&dir.path().join(".env.prod")).unwrap();
+        assert_eq!(prod.vars["NODE_ENV"], "production");
+
+        let scripts = [
+            ("build".to_string(), "webpack --mode $NODE_ENV".to_string()),
+            ("lint".to_string(), "eslint .".to_string()),
+        ];
+        let mut out = Vec::new();
+        print_comparison(&mut out, &scripts, &dev, &prod).unwrap();
+        let out = String::from_utf8(out).unwrap();
+
+        let dev_name = dev.path.display().to_string();
+        let prod_name = prod.path.display().to_string();
+        assert!(out.contains(&format!(
+            "      {}:  webpack --mode development\n",
+            dev_name
+        )));
+        assert!(out.contains(&format!("      {}: webpack --mode production\n", prod_name)));
+        assert!(!out.contains("eslint"));
+        assert!(out.ends_with("   1 script(s) render the same in both\n\n"));
+        assert!(EnvFile::load(&dir.path().join(".env.missing")).is_err());
+    }
+}
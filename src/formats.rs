@@ -0,0 +1,1358 @@
+use anyhow::{bail, Result};
+use serde::Serialize;
+use std::collections::{HashMap, HashSet};
+
+use crate::changes::ScriptDiff;
+use crate::package_manager::PackageManager;
+use crate::shell;
+
+#[derive(Serialize)]
+struct GithubActionsMatrix<'a> {
+    strategy: Strategy<'a>,
+}
+
+#[derive(Serialize)]
+struct Strategy<'a> {
+    matrix: Matrix<'a>,
+}
+
+#[derive(Serialize)]
+struct Matrix<'a> {
+    script: Vec<&'a str>,
+}
+
+/// `strategy.matrix` block fanning a job out over the scripts, used as
+/// `run: npm run ${{ matrix.script }}`
+pub fn github_actions_matrix(scripts: &[(String, String)]) -> Result<String> {
+    let matrix = GithubActionsMatrix {
+        strategy: Strategy {
+            matrix: Matrix {
+                script: scripts.iter().map(|(name, _)| name.as_str()).collect(),
+            },
+        },
+    };
+    Ok(serde_yaml::to_string(&matrix)?)
+}
+
+/// JSON Schema (draft-07) accepting exactly the defined script names, for
+/// validating `npm run <name>` calls in other tooling
+pub fn json_schema(scripts: &[(String, String)]) -> Result<String> {
+    let names: Vec<&str> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+    let schema = serde_json::json!({
+        "$schema": "http://json-schema.org/draft-07/schema#",
+        "title": "Script name",
+        "type": "string",
+        "enum": names,
+    });
+    Ok(serde_json::to_string_pretty(&schema)? + "\n")
+}
+
+#[derive(Serialize)]
+struct Completion<'a> {
+    name: &'a str,
+    command: &'a str,
+    /// The program the first step runs
+    runner: Option<String>,
+    pre: Option<String>,
+    post: Option<String>,
+    env_vars: Vec<String>,
+    /// The part of the name before the first `:`, as in `test:unit`
+    category: Option<&'a str>,
+}
+
+/// One object per script with what IDE task integrations want to know:
+/// the runner, `pre`/`post` lifecycle hooks among `names`, and the
+/// environment variables the command sets
+pub fn completions_json(scripts: &[(String, String)], names: &[&str]) -> Result<String> {
+    let hook = |prefix: &str, name: &str| {
+        let hook = format!("{}{}", prefix, name);
+        names.contains(&hook.as_str()).then_some(hook)
+    };
+    let completions: Vec<_> = scripts
+        .iter()
+        .map(|(name, command)| {
+            let (env, _) = shell::split_env_prefix(command);
+            Completion {
+                name,
+                command,
+                runner: shell::programs(command).into_iter().next(),
+                pre: hook("pre", name),
+                post: hook("post", name),
+                env_vars: env.into_iter().map(|(var, _)| var).collect(),
+                category: name.split_once(':').map(|(category, _)| category),
+            }
+        })
+        .collect();
+    Ok(serde_json::to_string_pretty(&completions)? + "\n")
+}
+
+/// Each script as `export` lines for its leading environment assignments
+/// followed by the rest of the command, ready to paste into a shell
+pub fn environment(scripts: &[(String, String)]) -> String {
+    let mut out = String::new();
+    for (i, (name, command)) in scripts.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        let (env, rest) = shell::split_env_prefix(command);
+        out.push_str(&format!("# {}\n", name));
+        for (var, value) in env {
+            out.push_str(&format!("export {}={}\n", var, value));
+        }
+        if !rest.is_empty() {
+            out.push_str(&format!("{}\n", rest));
+        }
+    }
+    out
+}
+
+/// Commented-out `RUN npm run <name>` lines documenting the scripts in a
+/// Dockerfile, each followed by its description (or its command when it has
+/// none). Everything is a comment, so pasting it changes nothing in a build.
+pub fn dockerfile_comments(
+    package_name: Option<&str>,
+    scripts: &[(String, String)],
+    descriptions: &HashMap<String, String>,
+) -> String {
+    let mut out = String::new();
+    if let Some(name) = package_name {
+        let name = name.replace('\\', "\\\\").replace('"', "\\\"");
+        out.push_str(&format!("# LABEL package=\"{}\"\n", name));
+    }
+    for (name, command) in scripts {
+        let description = descriptions.get(name).unwrap_or(command);
+        out.push_str(&format!("# RUN npm run {}\n", shell::quote(name)));
+        for line in description.lines() {
+            out.push_str(&format!("#   {}\n", line));
+        }
+    }
+    out
+}
+
+/// A Jira wiki markup table (`||heading||`, `|cell|`) under an `h2.`
+/// heading with the package name
+pub fn jira_wiki(package_name: Option<&str>, scripts: &[(String, String)]) -> String {
+    let cell = |text: &str| text.replace('|', "\\|");
+    let mut out = String::new();
+    if let Some(name) = package_name {
+        out.push_str(&format!("h2. {}\n\n", name));
+    }
+    out.push_str("||Script||Command||\n");
+    for (name, command) in scripts {
+        out.push_str(&format!("|{}|{}|\n", cell(name), cell(command)));
+    }
+    out
+}
+
+/// A line per script, `name  # command`, in the style of a requirements.txt.
+/// The comments line up after the longest name; a `#` in a command is left
+/// as it is, since the comment already runs to the end of the line.
+pub fn requirements_txt(scripts: &[(String, String)]) -> String {
+    let width = scripts
+        .iter()
+        .map(|(name, _)| name.chars().count())
+        .max()
+        .unwrap_or(0);
+    scripts
+        .iter()
+        .map(|(name, command)| format!("{:<width$}  # {}\n", name, command))
+        .collect()
+}
+
+/// A Nix attribute set, `{ scripts = { build = "tsc"; }; }`, for flakes to
+/// import. Names that aren't plain Nix identifiers (`build:css`) are quoted.
+pub fn nix(scripts: &[(String, String)]) -> String {
+    let mut out = String::from("{\n  scripts = {\n");
+    for (name, command) in scripts {
+        let name = if is_nix_identifier(name) {
+            name.clone()
+        } else {
+            nix_string(name)
+        };
+        out.push_str(&format!("    {} = {};\n", name, nix_string(command)));
+    }
+    out.push_str("  };\n}\n");
+    out
+}
+
+// `"..."` with the characters special inside one escaped; `${` would start
+// an interpolation
+fn nix_string(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace("${", "\\${")
+        .replace('\n', "\\n")
+        .replace('\r', "\\r")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+fn is_nix_identifier(name: &str) -> bool {
+    const KEYWORDS: &[&str] = &[
+        "assert", "else", "if", "in", "inherit", "let", "or", "rec", "then", "with",
+    ];
+    let mut chars = name.chars();
+    chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '\'' | '-'))
+        && !KEYWORDS.contains(&name)
+}
+
+/// A Bash completion function for `npm run <TAB>`, to `source` or `eval`.
+/// It asks `sl --format completion-helper` for the scripts each time, so it
+/// follows `cd` from one project to another.
+pub fn completions_bash() -> String {
+    r#"# npm run completion with the scripts of the package.json in the current
+# directory, from script-list
+_project_npm_run() {
+    local cur=${COMP_WORDS[COMP_CWORD]}
+    if [[ $COMP_CWORD -eq 2 && ( ${COMP_WORDS[1]} == run || ${COMP_WORDS[1]} == run-script ) ]]; then
+        COMPREPLY=($(compgen -W "$(sl --format completion-helper 2>/dev/null)" -- "$cur"))
+    fi
+}
+complete -F _project_npm_run npm
+"#
+    .to_string()
+}
+
+/// The bare script names, one per line, for completion functions
+pub fn completion_helper(scripts: &[(String, String)]) -> String {
+    scripts
+        .iter()
+        .map(|(name, _)| format!("{}\n", name))
+        .collect()
+}
+
+// Where `env_make` wraps long commands onto continuation lines
+const MAKE_LINE_WIDTH: usize = 80;
+
+/// A GNU Make assignment per script, `BUILD_CSS_CMD = sass src:dist`, to
+/// `include` from a Makefile. `$` and `#` are escaped, and long commands are
+/// wrapped with `\` continuations at single spaces, which Make joins back
+/// with a space. Fails if two scripts would share a variable, as Make would
+/// quietly keep only the last.
+pub fn env_make(scripts: &[(String, String)]) -> Result<String> {
+    let mut taken = HashMap::new();
+    let mut out = String::new();
+    for (name, command) in scripts {
+        let variable = make_variable(name);
+        if let Some(other) = taken.insert(variable.clone(), name) {
+            bail!("`{}` and `{}` would both be {}", other, name, variable);
+        }
+        let escaped = command.replace('$', "$$").replace('#', "\\#");
+        let line = format!("{} = {}", variable, escaped);
+        // Wrapping at a run of spaces would shrink it to one
+        if line.chars().count() <= MAKE_LINE_WIDTH || escaped.contains("  ") {
+            out.push_str(&line);
+            out.push('\n');
+            continue;
+        }
+        let mut width = 0;
+        for (i, word) in line.split(' ').enumerate() {
+            let word_width = word.chars().count();
+            if i > 0 && width + 1 + word_width + 2 > MAKE_LINE_WIDTH {
+                out.push_str(" \\\n\t");
+                width = 8;
+            } else if i > 0 {
+                out.push(' ');
+                width += 1;
+            }
+            out.push_str(word);
+            width += word_width;
+        }
+        out.push('\n');
+    }
+    Ok(out)
+}
+
+// `build:css` -> `BUILD_CSS_CMD`
+fn make_variable(name: &str) -> String {
+    let words: Vec<String> = name
+        .split(|c: char| !c.is_ascii_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .map(str::to_ascii_uppercase)
+        .collect();
+    words
+        .into_iter()
+        .chain(["CMD".to_string()])
+        .collect::<Vec<_>>()
+        .join("_")
+}
+
+#[derive(Serialize)]
+struct PostCreate {
+    #[serde(rename = "postCreateCommand")]
+    post_create_command: String,
+}
+
+/// A devcontainer.json snippet whose `postCreateCommand` runs `names` one
+/// after the other through `manager`, stopping at the first that fails
+pub fn devcontainer_post_create(names: &[&str], manager: PackageManager) -> Result<String> {
+    let command = names
+        .iter()
+        .map(|name| manager.command_line(name))
+        .collect::<Vec<_>>()
+        .join(" && ");
+    let snippet = PostCreate {
+        post_create_command: command,
+    };
+    Ok(serde_json::to_string_pretty(&snippet)? + "\n")
+}
+
+#[derive(Serialize)]
+struct AzurePipelines {
+    steps: Vec<AzurePipelinesStep>,
+}
+
+#[derive(Serialize)]
+#[serde(rename_all = "camelCase")]
+struct AzurePipelinesStep {
+    script: String,
+    display_name: String,
+}
+
+/// An Azure Pipelines `steps:` list with a `script:` step per script, run
+/// through `manager` and named after the script
+pub fn azure_pipelines(scripts: &[(String, String)], manager: PackageManager) -> Result<String> {
+    let steps = scripts
+        .iter()
+        .map(|(name, _)| AzurePipelinesStep {
+            script: manager.command_line(name),
+            display_name: name.clone(),
+        })
+        .collect();
+    Ok(serde_yaml::to_string(&AzurePipelines { steps })?)
+}
+
+#[derive(Serialize)]
+struct Circleci {
+    steps: Vec<CircleciStep>,
+}
+
+#[derive(Serialize)]
+struct CircleciStep {
+    run: CircleciRun,
+}
+
+#[derive(Serialize)]
+struct CircleciRun {
+    name: String,
+    command: String,
+}
+
+/// A CircleCI `steps:` list with a `run:` step per script, run through
+/// `manager` and named after the script
+pub fn circleci(scripts: &[(String, String)], manager: PackageManager) -> Result<String> {
+    let steps = scripts
+        .iter()
+        .map(|(name, _)| CircleciStep {
+            run: CircleciRun {
+                name: name.clone(),
+                command: manager.command_line(name),
+            },
+        })
+        .collect();
+    Ok(serde_yaml::to_string(&Circleci { steps })?)
+}
+
+/// A turbo.json template with a `pipeline` task per script, each with no
+/// `dependsOn` to start from and no `outputs` except `build`'s conventional
+/// `dist/**`
+pub fn turbo_pipeline(scripts: &[(String, String)]) -> Result<String> {
+    let pipeline: serde_json::Map<String, serde_json::Value> = scripts
+        .iter()
+        .map(|(name, _)| {
+            let outputs: &[&str] = if name == "build" { &["dist/**"] } else { &[] };
+            let task = serde_json::json!({ "dependsOn": [], "outputs": outputs });
+            (name.clone(), task)
+        })
+        .collect();
+    let turbo = serde_json::json!({
+        "$schema": "https://turbo.build/schema.json",
+        "pipeline": pipeline,
+    });
+    Ok(serde_json::to_string_pretty(&turbo)? + "\n")
+}
+
+// Nx executors for the programs scripts commonly start with
+const NX_EXECUTORS: &[(&str, &str)] = &[
+    ("node", "@nrwl/node:execute"),
+    ("ts-node", "@nrwl/node:execute"),
+    ("tsx", "@nrwl/node:execute"),
+    ("webpack", "@nrwl/node:execute"),
+    ("jest", "@nrwl/jest:jest"),
+    ("eslint", "@nrwl/linter:eslint"),
+    ("cypress", "@nrwl/cypress:cypress"),
+];
+
+// What runs whatever else isn't in `NX_EXECUTORS`
+const NX_FALLBACK_EXECUTOR: &str = "npm:run";
+
+/// A project.json with an Nx target per script, its executor picked by the
+/// program the command starts with and the command passed as an option
+pub fn nx_project(package_name: Option<&str>, scripts: &[(String, String)]) -> Result<String> {
+    let targets: serde_json::Map<String, serde_json::Value> = scripts
+        .iter()
+        .map(|(name, command)| {
+            let program = shell::programs(command).into_iter().next();
+            let executor = NX_EXECUTORS
+                .iter()
+                .find(|(known, _)| program.as_deref() == Some(*known))
+                .map_or(NX_FALLBACK_EXECUTOR, |(_, executor)| *executor);
+            let target = serde_json::json!({
+                "executor": executor,
+                "options": { "command": command },
+            });
+            (name.clone(), target)
+        })
+        .collect();
+    let mut project = serde_json::Map::new();
+    if let Some(name) = package_name {
+        project.insert("name".to_string(), name.into());
+    }
+    project.insert("targets".to_string(), targets.into());
+    Ok(serde_json::to_string_pretty(&project)? + "\n")
+}
+
+/// A `[tool.poe.tasks]` section for pyproject.toml with a task per script
+/// running it through `manager`. Task names keep letters, digits, `-` and
+/// `_`; anything else (`:` mostly) becomes `_`, plus a `_2`... if that
+/// clashes with another script's.
+pub fn poe_tasks(scripts: &[(String, String)], manager: PackageManager) -> String {
+    let mut out = String::from("[tool.poe.tasks]\n");
+    let mut taken = HashSet::new();
+    for (name, _) in scripts {
+        let base: String = name
+            .chars()
+            .map(|c| match c {
+                'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '_' => c,
+                _ => '_',
+            })
+            .collect();
+        let key = unique_name(&mut taken, base);
+        let cmd = toml_string(&manager.command_line(name));
+        out.push_str(&format!("{} = {{ cmd = {} }}\n", key, cmd));
+    }
+    out
+}
+
+/// Gradle `Exec` tasks for build.gradle, one per script, running it through
+/// `manager`. Task names keep to Groovy identifiers: anything but letters,
+/// digits and `_` becomes `_`, and clashes get a `_2`...
+pub fn gradle_tasks(scripts: &[(String, String)], manager: PackageManager) -> String {
+    let mut taken = HashSet::new();
+    scripts
+        .iter()
+        .map(|(name, _)| {
+            let mut base: String = name
+                .chars()
+                .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+                .collect();
+            if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                base.insert(0, '_');
+            }
+            let task = unique_name(&mut taken, base);
+            let args: Vec<String> = std::iter::once(manager.name().to_string())
+                .chain(manager.run_args(name, &[]))
+                .map(|arg| single_quoted(&arg))
+                .collect();
+            format!(
+                "task {}(type: Exec) {{\n    commandLine {}\n}}\n",
+                task,
+                args.join(", ")
+            )
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// A single-quoted Groovy or Ruby string
+fn single_quoted(text: &str) -> String {
+    format!("'{}'", text.replace('\\', "\\\\").replace('\'', "\\'"))
+}
+
+/// Rake tasks for a Rakefile, each `sh`-ing its script through `manager`.
+/// `build:prod` becomes task `prod` in `namespace :build`, and names that
+/// aren't plain Ruby symbols are quoted (`:"lint-fix"`).
+pub fn rake_tasks(scripts: &[(String, String)], manager: PackageManager) -> String {
+    let entries: Vec<(Vec<&str>, &str)> = scripts
+        .iter()
+        .map(|(name, _)| (name.split(':').collect(), name.as_str()))
+        .collect();
+    let mut out = String::new();
+    write_rake_level(&mut out, &entries, 0, manager);
+    out
+}
+
+enum RakeItem<'a> {
+    Task(&'a str, &'a str),
+    Namespace(&'a str, Vec<(Vec<&'a str>, &'a str)>),
+}
+
+// The tasks and namespaces of one level, in the order they first appear,
+// `entries` holding each script's name parts left at this level
+fn write_rake_level(
+    out: &mut String,
+    entries: &[(Vec<&str>, &str)],
+    depth: usize,
+    manager: PackageManager,
+) {
+    let mut items: Vec<RakeItem> = Vec::new();
+    for (parts, name) in entries {
+        let [first, rest @ ..] = parts.as_slice() else {
+            continue;
+        };
+        if rest.is_empty() {
+            items.push(RakeItem::Task(first, name));
+            continue;
+        }
+        let existing = items.iter_mut().find_map(|item| match item {
+            RakeItem::Namespace(namespace, children) if namespace == first => Some(children),
+            _ => None,
+        });
+        match existing {
+            Some(children) => children.push((rest.to_vec(), name)),
+            None => items.push(RakeItem::Namespace(first, vec![(rest.to_vec(), name)])),
+        }
+    }
+
+    let indent = "  ".repeat(depth);
+    for (i, item) in items.iter().enumerate() {
+        if i > 0 {
+            out.push('\n');
+        }
+        match item {
+            RakeItem::Task(task, name) => {
+                let command = single_quoted(&manager.command_line(name));
+                out.push_str(&format!("{}task {} do\n", indent, ruby_symbol(task)));
+                out.push_str(&format!("{}  sh {}\n", indent, command));
+            }
+            RakeItem::Namespace(namespace, children) => {
+                out.push_str(&format!(
+                    "{}namespace {} do\n",
+                    indent,
+                    ruby_symbol(namespace)
+                ));
+                write_rake_level(out, children, depth + 1, manager);
+            }
+        }
+        out.push_str(&format!("{}end\n", indent));
+    }
+}
+
+// `:build`, or `:"lint-fix"` for names that aren't bare symbols
+fn ruby_symbol(name: &str) -> String {
+    let mut chars = name.chars();
+    let plain = chars
+        .next()
+        .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_');
+    if plain {
+        format!(":{}", name)
+    } else {
+        let escaped = name
+            .replace('\\', "\\\\")
+            .replace('"', "\\\"")
+            .replace("#{", "\\#{");
+        format!(":\"{}\"", escaped)
+    }
+}
+
+/// A justfile with a recipe per script running it through `manager`. Every
+/// recipe is `@` quiet, as the package manager already echoes the script,
+/// and scripts that pass on their arguments (`"$@"`) take `*ARGS` to forward.
+pub fn justfile(scripts: &[(String, String)], manager: PackageManager) -> String {
+    const ARGS: &str = "{{ARGS}}";
+    let mut taken = HashSet::new();
+    scripts
+        .iter()
+        .map(|(name, command)| {
+            let mut base: String = name
+                .chars()
+                .map(|c| match c {
+                    'a'..='z' | 'A'..='Z' | '0'..='9' | '_' | '-' => c,
+                    _ => '-',
+                })
+                .collect();
+            if !base.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_') {
+                base.insert(0, '_');
+            }
+            let recipe = unique_name(&mut taken, base);
+            let forwards_args = command.contains("$@");
+            let args: &[String] = if forwards_args {
+                &[ARGS.to_string()]
+            } else {
+                &[]
+            };
+            let line = std::iter::once(manager.name().to_string())
+                .chain(manager.run_args(name, args))
+                .map(|word| {
+                    if word == ARGS {
+                        word
+                    } else {
+                        // `{{` starts an interpolation, `{{{{` is a literal one
+                        shell::quote(&word).replace("{{", "{{{{")
+                    }
+                })
+                .collect::<Vec<_>>()
+                .join(" ");
+            let params = if forwards_args { " *ARGS" } else { "" };
+            format!("@{}{}:\n    {}\n", recipe, params, line)
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+// `base`, or with `_2`, `_3`... added if another task already has it
+fn unique_name(taken: &mut HashSet<String>, base: String) -> String {
+    let mut name = base.clone();
+    for n in 2.. {
+        if taken.insert(name.clone()) {
+            break;
+        }
+        name = format!("{}_{}", base, n);
+    }
+    name
+}
+
+// A TOML basic string
+fn toml_string(text: &str) -> String {
+    let escaped = text
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t");
+    format!("\"{}\"", escaped)
+}
+
+/// OpenTelemetry span attributes per script (`npm.script.name`,
+/// `npm.script.command` and, if the package has a name, `package.name`): an
+/// object for a single script, or an array of them
+pub fn opentelemetry_attributes(
+    package_name: Option<&str>,
+    scripts: &[(String, String)],
+) -> Result<String> {
+    let mut spans: Vec<serde_json::Value> = scripts
+        .iter()
+        .map(|(name, command)| {
+            let mut attributes = serde_json::Map::new();
+            attributes.insert("npm.script.name".to_string(), name.as_str().into());
+            attributes.insert("npm.script.command".to_string(), command.as_str().into());
+            if let Some(package_name) = package_name {
+                attributes.insert("package.name".to_string(), package_name.into());
+            }
+            attributes.into()
+        })
+        .collect();
+    let json = if spans.len() == 1 {
+        spans.remove(0)
+    } else {
+        spans.into()
+    };
+    Ok(serde_json::to_string_pretty(&json)? + "\n")
+}
+
+/// A GitHub task list item per script, `- [ ] name — `command``, ticked for
+/// the scripts in `checked`
+pub fn checklist(scripts: &[(String, String)], checked: &[String]) -> String {
+    scripts
+        .iter()
+        .map(|(name, command)| {
+            let mark = if checked.contains(name) { "x" } else { " " };
+            format!("- [{}] {} — {}\n", mark, name, code_span(command))
+        })
+        .collect()
+}
+
+/// A GitHub PR comment body: a heading, a line naming the package, then the
+/// scripts as a Markdown table folded away in a `<details>` block. With
+/// `diff` (`--diff`), only the added, changed and removed scripts are listed.
+pub fn github_pr_comment(
+    package_name: Option<&str>,
+    version: Option<&str>,
+    scripts: &[(String, String)],
+    diff: Option<&ScriptDiff>,
+) -> String {
+    let mut out = String::new();
+    out.push_str(match diff {
+        Some(_) => "### Changed npm scripts\n\n",
+        None => "### npm scripts\n\n",
+    });
+    match (package_name, version) {
+        (Some(name), Some(version)) => {
+            out.push_str(&format!("Scripts of `{}` {}.\n\n", name, version))
+        }
+        (Some(name), None) => out.push_str(&format!("Scripts of `{}`.\n\n", name)),
+        _ => {}
+    }
+
+    let Some(diff) = diff else {
+        out.push_str(&format!(
+            "<details>\n<summary>{} script(s)</summary>\n\n",
+            scripts.len()
+        ));
+        out.push_str("| Script | Command |\n| --- | --- |\n");
+        for (name, command) in scripts {
+            out.push_str(&format!(
+                "| {} | {} |\n",
+                table_cell(name),
+                table_cell(command)
+            ));
+        }
+        out.push_str("\n</details>\n");
+        return out;
+    };
+
+    let mut rows: Vec<_> = scripts
+        .iter()
+        .filter_map(|(name, command)| {
+            let change = match diff.sigil(name)? {
+                '+' => "Added",
+                _ => "Changed",
+            };
+            Some((change, name, command))
+        })
+        .collect();
+    rows.extend(
+        diff.removed
+            .iter()
+            .map(|(name, command)| ("Removed", name, command)),
+    );
+    if rows.is_empty() {
+        out.push_str("No scripts changed.\n");
+        return out;
+    }
+    out.push_str(&format!(
+        "<details open>\n<summary>{} changed script(s)</summary>\n\n",
+        rows.len()
+    ));
+    out.push_str("| Change | Script | Command |\n| --- | --- | --- |\n");
+    for (change, name, command) in rows {
+        let (name, command) = (table_cell(name), table_cell(command));
+        out.push_str(&format!("| {} | {} | {} |\n", change, name, command));
+    }
+    out.push_str("\n</details>\n");
+    out
+}
+
+// Inline code; one with backticks of its own needs a longer fence
+fn code_span(text: &str) -> String {
+    if text.contains('`') {
+        format!("`` {} ``", text)
+    } else {
+        format!("`{}`", text)
+    }
+}
+
+// Inline code in a Markdown table, where even a `|` in code ends the cell
+fn table_cell(text: &str) -> String {
+    code_span(&text.replace('|', "\\|"))
+}
+
+/// The bare command and a single newline, for
+/// `eval "$(sl --script build --format plain-command)"`
+pub fn plain_command(command: &str) -> String {
+    format!("{}\n", command)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripts(names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), format!("run {}", name)))
+            .collect()
+    }
+
+    #[test]
+    fn test_github_actions_matrix() {
+        let yaml = github_actions_matrix(&scripts(&["build", "lint", "test:unit"])).unwrap();
+
+        let value: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        let names: Vec<&str> = value["strategy"]["matrix"]["script"]
+            .as_sequence()
+            .unwrap()
+            .iter()
+            .map(|v| v.as_str().unwrap())
+            .collect();
+        assert_eq!(names, ["build", "lint", "test:unit"]);
+    }
+
+    #[test]
+    fn test_json_schema() {
+        let schema = json_schema(&scripts(&["build", "lint", "test"])).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&schema).unwrap();
+        assert_eq!(value["$schema"], "http://json-schema.org/draft-07/schema#");
+        assert_eq!(value["type"], "string");
+        assert_eq!(value["enum"], serde_json::json!(["build", "lint", "test"]));
+    }
+
+    #[test]
+    fn test_plain_command() {
+        assert_eq!(
+            plain_command("NODE_ENV=production webpack"),
+            "NODE_ENV=production webpack\n"
+        );
+    }
+
+    #[test]
+    fn test_environment() {
+        let scripts = [
+            (
+                "dev".to_string(),
+                "NODE_ENV=development PORT=3000 vite".to_string(),
+            ),
+            ("lint".to_string(), "eslint .".to_string()),
+        ];
+        assert_eq!(
+            environment(&scripts),
+            "# dev\nexport NODE_ENV=development\nexport PORT=3000\nvite\n\n# lint\neslint .\n"
+        );
+    }
+
+    #[test]
+    fn test_dockerfile_comments() {
+        let descriptions = HashMap::from([("build".to_string(), "Compile to dist/".to_string())]);
+        let out = dockerfile_comments(
+            Some("@acme/web"),
+            &scripts(&["build", "test:unit"]),
+            &descriptions,
+        );
+
+        assert!(out.lines().all(|line| line.starts_with("# ")));
+        assert_eq!(
+            out,
+            "# LABEL package=\"@acme/web\"\n\
+             # RUN npm run build\n\
+             #   Compile to dist/\n\
+             # RUN npm run test:unit\n\
+             #   run test:unit\n"
+        );
+
+        let out = dockerfile_comments(Some(r#"ünï "q" \"#), &[], &HashMap::new());
+        assert_eq!(out, "# LABEL package=\"ünï \\\"q\\\" \\\\\"\n");
+    }
+
+    #[test]
+    fn test_completions_json() {
+        let scripts = [
+            (
+                "build".to_string(),
+                "NODE_ENV=production webpack --mode production".to_string(),
+            ),
+            ("test:unit".to_string(), "jest".to_string()),
+        ];
+        let names = ["build", "prebuild", "test:unit", "posttest:unit"];
+        let json = completions_json(&scripts, &names).unwrap();
+
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!([
+                {
+                    "name": "build",
+                    "command": "NODE_ENV=production webpack --mode production",
+                    "runner": "webpack",
+                    "pre": "prebuild",
+                    "post": null,
+                    "env_vars": ["NODE_ENV"],
+                    "category": null,
+                },
+                {
+                    "name": "test:unit",
+                    "command": "jest",
+                    "runner": "jest",
+                    "pre": null,
+                    "post": "posttest:unit",
+                    "env_vars": [],
+                    "category": "test",
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn test_jira_wiki() {
+        let scripts = [
+            ("build".to_string(), "tsc".to_string()),
+            ("log".to_string(), "git log | head -5".to_string()),
+        ];
+        assert_eq!(
+            jira_wiki(Some("my-app"), &scripts),
+            "h2. my-app\n\n||Script||Command||\n|build|tsc|\n|log|git log \\| head -5|\n"
+        );
+        assert!(jira_wiki(None, &scripts).starts_with("||Script||Command||\n"));
+    }
+
+    #[test]
+    fn test_requirements_txt() {
+        let scripts = [
+            ("build".to_string(), "webpack --mode production".to_string()),
+            ("lint:fix".to_string(), "eslint . --fix".to_string()),
+            (
+                "tag".to_string(),
+                "git tag \"#$(date +%s)\" # release".to_string(),
+            ),
+        ];
+        assert_eq!(
+            requirements_txt(&scripts),
+            "build     # webpack --mode production\n\
+             lint:fix  # eslint . --fix\n\
+             tag       # git tag \"#$(date +%s)\" # release\n"
+        );
+    }
+
+    #[test]
+    fn test_completions_bash() {
+        let script = completions_bash();
+        assert!(script.contains("\n_project_npm_run() {\n"));
+        assert!(script.contains("sl --format completion-helper"));
+        assert!(script.ends_with("}\ncomplete -F _project_npm_run npm\n"));
+
+        assert_eq!(
+            completion_helper(&scripts(&["build", "test:unit"])),
+            "build\ntest:unit\n"
+        );
+    }
+
+    #[test]
+    fn test_nix() {
+        let scripts = [
+            ("build".to_string(), "webpack".to_string()),
+            ("build:css".to_string(), "sass src:dist".to_string()),
+            ("in".to_string(), "echo".to_string()),
+            (
+                "clean".to_string(),
+                r#"rimraf "dist\*" && echo ${HOME}"#.to_string(),
+            ),
+        ];
+        assert_eq!(
+            nix(&scripts),
+            r#"{
+  scripts = {
+    build = "webpack";
+    "build:css" = "sass src:dist";
+    "in" = "echo";
+    clean = "rimraf \"dist\\*\" && echo \${HOME}";
+  };
+}
+"#
+        );
+        assert_eq!(nix(&[]), "{\n  scripts = {\n  };\n}\n");
+    }
+
+    #[test]
+    fn test_make_variable() {
+        assert_eq!(make_variable("build"), "BUILD_CMD");
+        assert_eq!(make_variable("build:css"), "BUILD_CSS_CMD");
+        assert_eq!(make_variable("test:e2e-ci"), "TEST_E2E_CI_CMD");
+        assert_eq!(make_variable("lint--fix"), "LINT_FIX_CMD");
+        assert_eq!(make_variable("@scope/build.prod"), "SCOPE_BUILD_PROD_CMD");
+    }
+
+    #[test]
+    fn test_env_make() {
+        let scripts = [
+            (
+                "build".to_string(),
+                "webpack --mode production".to_string(),
+            ),
+            ("home".to_string(), "echo $HOME # where".to_string()),
+            (
+                "test:e2e".to_string(),
+                "start-server-and-test 'npm run serve' http://localhost:8080 'cypress run --browser chrome'"
+                    .to_string(),
+            ),
+        ];
+        assert_eq!(
+            env_make(&scripts).unwrap(),
+            "BUILD_CMD = webpack --mode production\n\
+             HOME_CMD = echo $$HOME \\# where\n\
+             TEST_E2E_CMD = start-server-and-test 'npm run serve' http://localhost:8080 \\\n\
+             \t'cypress run --browser chrome'\n"
+        );
+        assert!(env_make(&scripts)
+            .unwrap()
+            .lines()
+            .all(|line| line.chars().count() <= MAKE_LINE_WIDTH));
+
+        let colliding = [
+            ("build:css".to_string(), "sass".to_string()),
+            ("build-css".to_string(), "sass --watch".to_string()),
+        ];
+        assert_eq!(
+            env_make(&colliding).unwrap_err().to_string(),
+            "`build:css` and `build-css` would both be BUILD_CSS_CMD"
+        );
+    }
+
+    #[test]
+    fn test_devcontainer_post_create() {
+        let json = devcontainer_post_create(&["setup", "build"], PackageManager::Npm).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "postCreateCommand": "npm run setup && npm run build" })
+        );
+
+        let json = devcontainer_post_create(&["db:seed"], PackageManager::Pnpm).unwrap();
+        assert_eq!(
+            json,
+            "{\n  \"postCreateCommand\": \"pnpm run db:seed\"\n}\n"
+        );
+    }
+
+    #[test]
+    fn test_opentelemetry_attributes() {
+        let one = [("build".to_string(), "webpack".to_string())];
+        let json = opentelemetry_attributes(Some("my-app"), &one).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({
+                "npm.script.name": "build",
+                "npm.script.command": "webpack",
+                "package.name": "my-app"
+            })
+        );
+
+        let json = opentelemetry_attributes(None, &scripts(&["build", "test"])).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let spans = value.as_array().unwrap();
+        assert_eq!(spans.len(), 2);
+        assert!(spans.iter().all(|span| span["npm.script.name"].is_string()));
+        assert!(spans[1].get("package.name").is_none());
+    }
+
+    #[test]
+    fn test_azure_pipelines() {
+        let scripts = [
+            ("build".to_string(), "tsc".to_string()),
+            ("test:unit".to_string(), "jest".to_string()),
+        ];
+        let yaml = azure_pipelines(&scripts, PackageManager::Npm).unwrap();
+        assert_eq!(
+            yaml,
+            "steps:\n\
+             - script: npm run build\n\
+             \x20 displayName: build\n\
+             - script: npm run test:unit\n\
+             \x20 displayName: test:unit\n"
+        );
+
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        let steps = parsed["steps"].as_sequence().unwrap();
+        assert_eq!(steps.len(), 2);
+        for (step, (name, _)) in steps.iter().zip(&scripts) {
+            assert_eq!(
+                step["script"].as_str(),
+                Some(format!("npm run {}", name).as_str())
+            );
+            assert_eq!(step["displayName"].as_str(), Some(name.as_str()));
+        }
+    }
+
+    #[test]
+    fn test_circleci() {
+        let scripts = [
+            ("build".to_string(), "tsc".to_string()),
+            ("test:unit".to_string(), "jest".to_string()),
+        ];
+        let yaml = circleci(&scripts, PackageManager::Yarn).unwrap();
+        assert_eq!(
+            yaml,
+            "steps:\n\
+             - run:\n\
+             \x20   name: build\n\
+             \x20   command: yarn run build\n\
+             - run:\n\
+             \x20   name: test:unit\n\
+             \x20   command: yarn run test:unit\n"
+        );
+
+        let parsed: serde_yaml::Value = serde_yaml::from_str(&yaml).unwrap();
+        let steps = parsed["steps"].as_sequence().unwrap();
+        assert_eq!(steps.len(), 2);
+        for (step, (name, _)) in steps.iter().zip(&scripts) {
+            let run = &step["run"];
+            assert_eq!(run["name"].as_str(), Some(name.as_str()));
+            assert_eq!(
+                run["command"].as_str(),
+                Some(format!("yarn run {}", name).as_str())
+            );
+        }
+    }
+
+    #[test]
+    fn test_turbo_pipeline() {
+        let scripts = [
+            ("build".to_string(), "tsc".to_string()),
+            ("build:css".to_string(), "sass src:dist".to_string()),
+            ("lint".to_string(), "eslint .".to_string()),
+        ];
+        let json = turbo_pipeline(&scripts).unwrap();
+        let turbo: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(
+            turbo["pipeline"],
+            serde_json::json!({
+                "build": { "dependsOn": [], "outputs": ["dist/**"] },
+                "build:css": { "dependsOn": [], "outputs": [] },
+                "lint": { "dependsOn": [], "outputs": [] }
+            })
+        );
+        let tasks: Vec<_> = turbo["pipeline"].as_object().unwrap().keys().collect();
+        assert_eq!(tasks, ["build", "build:css", "lint"]);
+    }
+
+    #[test]
+    fn test_nx_project() {
+        let scripts = [
+            ("build".to_string(), "webpack".to_string()),
+            ("lint".to_string(), "eslint src".to_string()),
+            ("release".to_string(), "np --yolo".to_string()),
+            ("test".to_string(), "NODE_ENV=test jest".to_string()),
+        ];
+        let project: serde_json::Value =
+            serde_json::from_str(&nx_project(Some("web"), &scripts).unwrap()).unwrap();
+        assert_eq!(project["name"], "web");
+        assert_eq!(
+            project["targets"]["build"],
+            serde_json::json!({
+                "executor": "@nrwl/node:execute",
+                "options": { "command": "webpack" }
+            })
+        );
+        assert_eq!(
+            project["targets"]["lint"]["executor"],
+            "@nrwl/linter:eslint"
+        );
+        assert_eq!(project["targets"]["test"]["executor"], "@nrwl/jest:jest");
+        assert_eq!(
+            project["targets"]["release"],
+            serde_json::json!({
+                "executor": NX_FALLBACK_EXECUTOR,
+                "options": { "command": "np --yolo" }
+            })
+        );
+        assert_eq!(project["targets"].as_object().unwrap().len(), 4);
+    }
+
+    #[test]
+    fn test_poe_tasks() {
+        let scripts = [
+            ("build:css".to_string(), "sass".to_string()),
+            ("build_css".to_string(), "postcss".to_string()),
+            ("lint-fix".to_string(), "eslint --fix".to_string()),
+            ("say \"hi\"".to_string(), "echo hi".to_string()),
+        ];
+        let section = poe_tasks(&scripts, PackageManager::Npm);
+        assert_eq!(
+            section,
+            "[tool.poe.tasks]\n\
+             build_css = { cmd = \"npm run build:css\" }\n\
+             build_css_2 = { cmd = \"npm run build_css\" }\n\
+             lint-fix = { cmd = \"npm run lint-fix\" }\n\
+             say__hi_ = { cmd = \"npm run 'say \\\"hi\\\"'\" }\n"
+        );
+
+        let parsed: toml::Table = toml::from_str(&section).unwrap();
+        let tasks = parsed["tool"]["poe"]["tasks"].as_table().unwrap();
+        let keys: Vec<_> = tasks.keys().map(String::as_str).collect();
+        assert_eq!(keys, ["build_css", "build_css_2", "lint-fix", "say__hi_"]);
+        assert_eq!(
+            tasks["say__hi_"]["cmd"].as_str(),
+            Some("npm run 'say \"hi\"'")
+        );
+    }
+
+    #[test]
+    fn test_gradle_tasks() {
+        let scripts = [
+            ("build".to_string(), "webpack".to_string()),
+            ("lint-fix".to_string(), "eslint --fix".to_string()),
+            ("lint_fix".to_string(), "eslint --fix .".to_string()),
+            ("2fa's".to_string(), "node otp.js".to_string()),
+        ];
+        let gradle = gradle_tasks(&scripts, PackageManager::Npm);
+        assert_eq!(
+            gradle,
+            "task build(type: Exec) {\n\
+             \x20   commandLine 'npm', 'run', 'build'\n\
+             }\n\
+             \n\
+             task lint_fix(type: Exec) {\n\
+             \x20   commandLine 'npm', 'run', 'lint-fix'\n\
+             }\n\
+             \n\
+             task lint_fix_2(type: Exec) {\n\
+             \x20   commandLine 'npm', 'run', 'lint_fix'\n\
+             }\n\
+             \n\
+             task _2fa_s(type: Exec) {\n\
+             \x20   commandLine 'npm', 'run', '2fa\\'s'\n\
+             }\n"
+        );
+        assert_eq!(gradle.matches("(type: Exec) {").count(), 4);
+    }
+
+    #[test]
+    fn test_rake_tasks() {
+        let scripts: Vec<_> = [
+            "build",
+            "build:prod",
+            "lint-fix",
+            "build:dev",
+            "test:unit:watch",
+        ]
+        .iter()
+        .map(|name| (name.to_string(), String::new()))
+        .collect();
+        assert_eq!(
+            rake_tasks(&scripts, PackageManager::Npm),
+            "task :build do\n\
+             \x20 sh 'npm run build'\n\
+             end\n\
+             \n\
+             namespace :build do\n\
+             \x20 task :prod do\n\
+             \x20   sh 'npm run build:prod'\n\
+             \x20 end\n\
+             \n\
+             \x20 task :dev do\n\
+             \x20   sh 'npm run build:dev'\n\
+             \x20 end\n\
+             end\n\
+             \n\
+             task :\"lint-fix\" do\n\
+             \x20 sh 'npm run lint-fix'\n\
+             end\n\
+             \n\
+             namespace :test do\n\
+             \x20 namespace :unit do\n\
+             \x20   task :watch do\n\
+             \x20     sh 'npm run test:unit:watch'\n\
+             \x20   end\n\
+             \x20 end\n\
+             end\n"
+        );
+        assert_eq!(ruby_symbol("db_seed"), ":db_seed");
+        assert_eq!(ruby_symbol("2fa"), ":\"2fa\"");
+        assert_eq!(ruby_symbol(r##"say "#{x}""##), r##":"say \"\#{x}\"""##);
+    }
+
+    #[test]
+    fn test_justfile() {
+        let scripts = [
+            ("build".to_string(), "webpack".to_string()),
+            ("build:css".to_string(), "sass src:dist".to_string()),
+            ("test".to_string(), "jest \"$@\"".to_string()),
+            ("2fa".to_string(), "node otp.js".to_string()),
+        ];
+        let just = justfile(&scripts, PackageManager::Npm);
+        assert_eq!(
+            just,
+            "@build:\n\
+             \x20   npm run build\n\
+             \n\
+             @build-css:\n\
+             \x20   npm run build:css\n\
+             \n\
+             @test *ARGS:\n\
+             \x20   npm run test -- {{ARGS}}\n\
+             \n\
+             @_2fa:\n\
+             \x20   npm run 2fa\n"
+        );
+
+        // Every recipe a quiet header with a valid name, then one indented line
+        for recipe in just.split("\n\n") {
+            let mut lines = recipe.lines();
+            let header = lines.next().unwrap();
+            let name = header
+                .strip_prefix('@')
+                .unwrap()
+                .split([' ', ':'])
+                .next()
+                .unwrap();
+            assert!(name.starts_with(|c: char| c.is_ascii_alphabetic() || c == '_'));
+            assert!(name
+                .chars()
+                .all(|c| c.is_ascii_alphanumeric() || "_-".contains(c)));
+            assert!(header.ends_with(':'));
+            assert!(lines.all(|line| line.starts_with("    ")));
+        }
+
+        let yarn = justfile(&scripts[2..3], PackageManager::Yarn);
+        assert_eq!(yarn, "@test *ARGS:\n    yarn run test {{ARGS}}\n");
+    }
+
+    #[test]
+    fn test_github_pr_comment() {
+        let scripts = [
+            ("build".to_string(), "tsc".to_string()),
+            ("log".to_string(), "git log | head -5".to_string()),
+        ];
+        let comment = github_pr_comment(Some("my-app"), Some("1.2.0"), &scripts, None);
+        assert!(comment.starts_with("### "));
+        assert_eq!(
+            comment,
+            "### npm scripts\n\n\
+             Scripts of `my-app` 1.2.0.\n\n\
+             <details>\n<summary>2 script(s)</summary>\n\n\
+             | Script | Command |\n\
+             | --- | --- |\n\
+             | `build` | `tsc` |\n\
+             | `log` | `git log \\| head -5` |\n\n\
+             </details>\n"
+        );
+    }
+
+    #[test]
+    fn test_github_pr_comment_diff() {
+        let scripts = [
+            ("build".to_string(), "tsc -b".to_string()),
+            ("lint".to_string(), "eslint .".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let diff = ScriptDiff {
+            added: ["lint".to_string()].into(),
+            changed: ["build".to_string()].into(),
+            removed: vec![("e2e".to_string(), "cypress run".to_string())],
+        };
+        assert_eq!(
+            github_pr_comment(None, None, &scripts, Some(&diff)),
+            "### Changed npm scripts\n\n\
+             <details open>\n<summary>3 changed script(s)</summary>\n\n\
+             | Change | Script | Command |\n\
+             | --- | --- | --- |\n\
+             | Changed | `build` | `tsc -b` |\n\
+             | Added | `lint` | `eslint .` |\n\
+             | Removed | `e2e` | `cypress run` |\n\n\
+             </details>\n"
+        );
+        assert!(
+            github_pr_comment(None, None, &scripts, Some(&ScriptDiff::default()))
+                .ends_with("\n\nNo scripts changed.\n")
+        );
+    }
+
+    #[test]
+    fn test_checklist() {
+        let scripts = [
+            ("build".to_string(), "tsc".to_string()),
+            ("lint".to_string(), "eslint .".to_string()),
+            ("rev".to_string(), "echo `git rev-parse HEAD`".to_string()),
+        ];
+        assert_eq!(
+            checklist(&scripts, &["lint".to_string()]),
+            "- [ ] build — `tsc`\n\
+             - [x] lint — `eslint .`\n\
+             - [ ] rev — `` echo `git rev-parse HEAD` ``\n"
+        );
+    }
+}
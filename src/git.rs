@@ -0,0 +1,64 @@
+use std::path::Path;
+use std::process::Command;
+
+/// The tag pointing exactly at HEAD in `dir`, if any. Not being in a git
+/// repo, having no tags, or not having git at all all count as no tag.
+pub fn head_tag(dir: &Path) -> Option<String> {
+    let output = Command::new("git")
+        .args(["describe", "--tags", "--exact-match", "HEAD"])
+        .current_dir(dir)
+        .output()
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    let tag = String::from_utf8_lossy(&output.stdout).trim().to_string();
+    (!tag.is_empty()).then_some(tag)
+}
+
+/// Warn when HEAD is tagged with a version other than `version`. Tags may
+/// carry the usual `v` prefix (`v1.2.0` matches `1.2.0`).
+pub fn check_version_tag(dir: &Path, version: &str) -> Option<String> {
+    let tag = head_tag(dir)?;
+    let tagged = tag.strip_prefix('v').unwrap_or(&tag);
+    (tagged != version).then(|| {
+        format!(
+            "package.json version {} doesn't match git tag {}, forgot to bump it?",
+            version, tag
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn git(dir: &Path, args: &[&str]) {
+        let status = Command::new("git")
+            .args(["-c", "user.name=sl", "-c", "user.email=sl@example.com"])
+            .args(args)
+            .current_dir(dir)
+            .output()
+            .unwrap()
+            .status;
+        assert!(status.success(), "git {:?} failed", args);
+    }
+
+    #[test]
+    fn test_check_version_tag() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(head_tag(dir.path()), None);
+
+        git(dir.path(), &["init", "-q"]);
+        git(dir.path(), &["commit", "-q", "--allow-empty", "-m", "init"]);
+        assert_eq!(check_version_tag(dir.path(), "1.0.0"), None);
+
+        git(dir.path(), &["tag", "v1.1.0"]);
+        assert_eq!(head_tag(dir.path()).as_deref(), Some("v1.1.0"));
+        assert_eq!(check_version_tag(dir.path(), "1.1.0"), None);
+        assert_eq!(
+            check_version_tag(dir.path(), "1.0.0").as_deref(),
+            Some("package.json version 1.0.0 doesn't match git tag v1.1.0, forgot to bump it?")
+        );
+    }
+}
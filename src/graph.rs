@@ -0,0 +1,294 @@
+use petgraph::algo::tarjan_scc;
+use petgraph::graph::{DiGraph, NodeIndex};
+use petgraph::Direction;
+use std::collections::{HashMap, HashSet, VecDeque};
+
+use crate::shell;
+
+// Scripts npm runs without the `run` keyword
+const NPM_SHORTHANDS: &[&str] = &["test", "start", "stop", "restart"];
+
+/// Names of the scripts `command` runs through a package manager
+/// (`npm run build`, `yarn lint`, `pnpm test`), limited to `names` so that
+/// `yarn add` or `npm install` aren't mistaken for scripts
+pub fn script_references(command: &str, names: &[&str]) -> Vec<String> {
+    let mut references = Vec::new();
+    for words in shell::simple_commands(command) {
+        let words: Vec<&str> = words
+            .iter()
+            .map(String::as_str)
+            .skip_while(|word| shell::is_assignment(word))
+            .collect();
+
+        let target = match words.as_slice() {
+            [pm, "run" | "run-script", name, ..] if is_package_manager(pm) => Some(*name),
+            ["npm", name, ..] if NPM_SHORTHANDS.contains(name) => Some(*name),
+            ["yarn" | "pnpm" | "bun", name, ..] => Some(*name),
+            _ => None,
+        };
+        if let Some(name) = target {
+            if names.contains(&name) && !references.iter().any(|r| r == name) {
+                references.push(name.to_string());
+            }
+        }
+    }
+    references
+}
+
+/// Graphviz DOT graph of which scripts run which: `runs` edges from
+/// `script_references`, and `pre`/`post` edges to lifecycle hooks. Cycles
+/// are drawn like any other edge.
+pub fn dot(scripts: &[(String, String)], names: &[&str]) -> String {
+    let mut out = String::from("digraph scripts {\n    rankdir=LR;\n    node [shape=box];\n");
+    for (name, _) in scripts {
+        out.push_str(&format!("    {};\n", dot_id(name)));
+    }
+    for (name, command) in scripts {
+        let mut edges: Vec<(String, &str)> = script_references(command, names)
+            .into_iter()
+            .map(|target| (target, "runs"))
+            .collect();
+        for hook in ["pre", "post"] {
+            let hook_name = format!("{}{}", hook, name);
+            if names.contains(&hook_name.as_str()) {
+                edges.push((hook_name, hook));
+            }
+        }
+        for (target, label) in edges {
+            out.push_str(&format!(
+                "    {} -> {} [label={}];\n",
+                dot_id(name),
+                dot_id(&target),
+                dot_id(label)
+            ));
+        }
+    }
+    out.push_str("}\n");
+    out
+}
+
+/// Which scripts run which: a node per script, in `scripts` order, and an
+/// edge from each script to the ones `script_references` finds it running
+pub fn build_script_graph(scripts: &[(String, String)]) -> DiGraph<String, ()> {
+    let names: Vec<&str> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+    let mut graph = DiGraph::new();
+    let nodes: Vec<NodeIndex> = scripts
+        .iter()
+        .map(|(name, _)| graph.add_node(name.clone()))
+        .collect();
+    for (node, (_, command)) in nodes.iter().zip(scripts) {
+        for target in script_references(command, &names) {
+            let target = names.iter().position(|name| *name == target).unwrap();
+            graph.add_edge(*node, nodes[target], ());
+        }
+    }
+    graph
+}
+
+/// A cycle per group of scripts that end up running each other, as the
+/// names along it back to the first (`["a", "b", "a"]`)
+pub fn cycles(graph: &DiGraph<String, ()>) -> Vec<Vec<String>> {
+    let mut cycles: Vec<_> = tarjan_scc(graph)
+        .into_iter()
+        .filter(|group| group.len() > 1 || graph.contains_edge(group[0], group[0]))
+        .map(|group| {
+            let start = *group.iter().min().unwrap();
+            shortest_cycle(graph, start, &group)
+                .into_iter()
+                .map(|node| graph[node].clone())
+                .collect()
+        })
+        .collect();
+    cycles.sort();
+    cycles
+}
+
+// Breadth-first from `start` through `group` until an edge leads back to it
+fn shortest_cycle(
+    graph: &DiGraph<String, ()>,
+    start: NodeIndex,
+    group: &[NodeIndex],
+) -> Vec<NodeIndex> {
+    let mut parents = HashMap::new();
+    let mut queue = VecDeque::from([start]);
+    while let Some(node) = queue.pop_front() {
+        for next in children(graph, node) {
+            if next == start {
+                let mut path = vec![start];
+                let mut step = node;
+                while step != start {
+                    path.push(step);
+                    step = parents[&step];
+                }
+                path.push(start);
+                path.reverse();
+                return path;
+            }
+            if group.contains(&next) && !parents.contains_key(&next) {
+                parents.insert(next, node);
+                queue.push_back(next);
+            }
+        }
+    }
+    vec![start]
+}
+
+/// `graph` as a text tree under each script nothing else runs, then any
+/// scripts only reachable through a cycle. A script running one already
+/// above it is marked `(cycle)` rather than expanded again.
+pub fn tree(graph: &DiGraph<String, ()>) -> String {
+    let mut out = String::new();
+    let mut shown = HashSet::new();
+    let roots = graph.node_indices().filter(|&node| {
+        graph
+            .neighbors_directed(node, Direction::Incoming)
+            .next()
+            .is_none()
+    });
+    for root in roots.chain(graph.node_indices()) {
+        if shown.contains(&root) {
+            continue;
+        }
+        out.push_str(&graph[root]);
+        out.push('\n');
+        write_children(&mut out, graph, root, "", &mut vec![root], &mut shown);
+    }
+    out
+}
+
+fn write_children(
+    out: &mut String,
+    graph: &DiGraph<String, ()>,
+    node: NodeIndex,
+    indent: &str,
+    path: &mut Vec<NodeIndex>,
+    shown: &mut HashSet<NodeIndex>,
+) {
+    shown.insert(node);
+    let children = children(graph, node);
+    for (i, child) in children.iter().enumerate() {
+        let last = i + 1 == children.len();
+        let branch = if last { "└── " } else { "├── " };
+        out.push_str(&format!("{}{}{}", indent, branch, graph[*child]));
+        if path.contains(child) {
+            out.push_str(" (cycle)\n");
+            continue;
+        }
+        out.push('\n');
+        let indent = format!("{}{}", indent, if last { "    " } else { "│   " });
+        path.push(*child);
+        write_children(out, graph, *child, &indent, path, shown);
+        path.pop();
+    }
+}
+
+// In script order; petgraph lists the newest edge first
+fn children(graph: &DiGraph<String, ()>, node: NodeIndex) -> Vec<NodeIndex> {
+    let mut children: Vec<_> = graph.neighbors(node).collect();
+    children.sort();
+    children
+}
+
+// Script names like `build:css` aren't plain DOT identifiers; quote them all
+fn dot_id(name: &str) -> String {
+    format!("\"{}\"", name.replace('\\', "\\\\").replace('"', "\\\""))
+}
+
+fn is_package_manager(program: &str) -> bool {
+    matches!(program, "npm" | "yarn" | "pnpm" | "bun")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_script_references() {
+        let names = ["build", "build:css", "lint", "test"];
+        assert_eq!(
+            script_references(
+                "npm run build:css && yarn lint && NODE_ENV=test pnpm run test",
+                &names
+            ),
+            ["build:css", "lint", "test"]
+        );
+        assert_eq!(
+            script_references("npm test && npm run test", &names),
+            ["test"]
+        );
+        assert!(script_references("yarn add lodash && npm install", &names).is_empty());
+        assert!(script_references("tsc && echo npm run build", &names).is_empty());
+    }
+
+    #[test]
+    fn test_dot() {
+        let scripts: Vec<_> = [
+            ("build", "npm run build:css && tsc"),
+            ("build:css", "sass src:dist"),
+            ("prebuild", "rimraf dist"),
+            ("watch", "npm run watch"),
+        ]
+        .iter()
+        .map(|(name, command)| (name.to_string(), command.to_string()))
+        .collect();
+        let names: Vec<&str> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+
+        let dot = dot(&scripts, &names);
+        assert!(dot.starts_with("digraph scripts {\n"));
+        assert!(dot.contains("    \"build\" -> \"build:css\" [label=\"runs\"];\n"));
+        assert!(dot.contains("    \"build\" -> \"prebuild\" [label=\"pre\"];\n"));
+        assert!(dot.contains("    \"watch\" -> \"watch\" [label=\"runs\"];\n"));
+        assert!(dot.ends_with("}\n"));
+        assert_eq!(dot_id(r#"say "hi""#), r#""say \"hi\"""#);
+    }
+
+    fn scripts(list: &[(&str, &str)]) -> Vec<(String, String)> {
+        list.iter()
+            .map(|(name, command)| (name.to_string(), command.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_build_script_graph_chains() {
+        let graph = build_script_graph(&scripts(&[
+            ("build:all", "npm run build:js && yarn build:css"),
+            ("build:js", "pnpm run clean && tsc"),
+            ("build:css", "sass src:dist"),
+            ("clean", "rimraf dist"),
+            ("lint", "eslint ."),
+        ]));
+        assert_eq!(graph.node_count(), 5);
+        assert_eq!(graph.edge_count(), 3);
+        assert!(cycles(&graph).is_empty());
+        assert_eq!(
+            tree(&graph),
+            "build:all\n\
+             ├── build:js\n\
+             │   └── clean\n\
+             └── build:css\n\
+             lint\n"
+        );
+    }
+
+    #[test]
+    fn test_cycles() {
+        let graph = build_script_graph(&scripts(&[
+            ("a", "npm run b"),
+            ("b", "npm run c"),
+            ("c", "npm run a"),
+            ("d", "npm run d"),
+            ("e", "npm run a"),
+        ]));
+        assert_eq!(cycles(&graph), [vec!["a", "b", "c", "a"], vec!["d", "d"]]);
+        assert_eq!(
+            tree(&graph),
+            "e\n\
+             └── a\n\
+             \x20   └── b\n\
+             \x20       └── c\n\
+             \x20           └── a (cycle)\n\
+             d\n\
+             └── d (cycle)\n"
+        );
+    }
+}
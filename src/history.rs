@@ -0,0 +1,175 @@
+use anyhow::{bail, Context, Result};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::env;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+/// One `--run` invocation
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Entry {
+    pub script: String,
+    /// Absolute path of the package.json the script came from
+    pub package: PathBuf,
+    /// Seconds since the Unix epoch
+    pub timestamp: u64,
+}
+
+/// Per-user directory for state kept between runs
+pub fn state_dir() -> Option<PathBuf> {
+    if let Some(dir) = env::var_os("XDG_STATE_HOME").filter(|d| !d.is_empty()) {
+        return Some(PathBuf::from(dir).join("script-list"));
+    }
+    if cfg!(windows) {
+        if let Some(dir) = env::var_os("LOCALAPPDATA") {
+            return Some(PathBuf::from(dir).join("script-list"));
+        }
+    }
+    env::var_os("HOME").map(|home| PathBuf::from(home).join(".local/state/script-list"))
+}
+
+/// Where run history is kept, one JSON entry per line
+pub fn history_file() -> Option<PathBuf> {
+    state_dir().map(|dir| dir.join("history.jsonl"))
+}
+
+/// How a package is identified in the history: its absolute package.json path
+pub fn package_key(package_path: &Path) -> PathBuf {
+    package_path
+        .canonicalize()
+        .unwrap_or_else(|_| package_path.to_path_buf())
+}
+
+pub fn record(history_file: &Path, package: &Path, script: &str) -> Result<()> {
+    let entry = Entry {
+        script: script.to_string(),
+        package: package.to_path_buf(),
+        timestamp: SystemTime::now().duration_since(UNIX_EPOCH)?.as_secs(),
+    };
+
+    if let Some(dir) = history_file.parent() {
+        fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    }
+    let mut file = fs::File::options()
+        .create(true)
+        .append(true)
+        .open(history_file)
+        .with_context(|| format!("Failed to open {}", history_file.display()))?;
+    writeln!(file, "{}", serde_json::to_string(&entry)?)?;
+    Ok(())
+}
+
+/// All recorded entries; a missing file is an empty history, and lines that
+/// don't parse (say, from a newer version) are skipped
+pub fn read(history_file: &Path) -> Result<Vec<Entry>> {
+    let content = match fs::read_to_string(history_file) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => {
+            return Err(e).with_context(|| format!("Failed to read {}", history_file.display()))
+        }
+    };
+    Ok(content
+        .lines()
+        .filter_map(|line| serde_json::from_str(line).ok())
+        .collect())
+}
+
+/// Names of the scripts in `package` that were run within `since` of now
+pub fn scripts_run_since(entries: &[Entry], package: &Path, since: Duration) -> HashSet<String> {
+    let now = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let cutoff = now.saturating_sub(since.as_secs());
+    entries
+        .iter()
+        .filter(|entry| entry.package == package && entry.timestamp >= cutoff)
+        .map(|entry| entry.script.clone())
+        .collect()
+}
+
+/// Parse durations like `90s`, `30m`, `12h`, `7d` or `2w`
+pub fn parse_duration(s: &str) -> Result<Duration> {
+    let split = s.find(|c: char| !c.is_ascii_digit()).unwrap_or(s.len());
+    let (amount, unit) = s.split_at(split);
+    let seconds = match unit {
+        "s" => Some(1),
+        "m" => Some(60),
+        "h" => Some(60 * 60),
+        "d" => Some(24 * 60 * 60),
+        "w" => Some(7 * 24 * 60 * 60),
+        _ => None,
+    };
+    // Too many seconds to count is as invalid as a missing unit
+    let total = match (amount.parse::<u64>(), seconds) {
+        (Ok(amount), Some(seconds)) => amount.checked_mul(seconds),
+        _ => None,
+    };
+    match total {
+        Some(total) => Ok(Duration::from_secs(total)),
+        None => bail!(
+            "invalid duration `{}`: expected a number followed by s, m, h, d or w (e.g. 7d)",
+            s
+        ),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_duration() {
+        assert_eq!(parse_duration("90s").unwrap(), Duration::from_secs(90));
+        assert_eq!(
+            parse_duration("7d").unwrap(),
+            Duration::from_secs(7 * 86400)
+        );
+        assert_eq!(
+            parse_duration("2w").unwrap(),
+            Duration::from_secs(14 * 86400)
+        );
+        assert!(parse_duration("7").is_err());
+        assert!(parse_duration("d").is_err());
+        assert!(parse_duration("7 days").is_err());
+        assert!(parse_duration("-1d").is_err());
+        assert!(parse_duration("99999999999999999w")
+            .unwrap_err()
+            .to_string()
+            .starts_with("invalid duration"));
+    }
+
+    #[test]
+    fn test_scripts_run_since() {
+        let dir = tempfile::tempdir().unwrap();
+        let history_file = dir.path().join("state/history.jsonl");
+        let app = Path::new("/work/app/package.json");
+        let other = Path::new("/work/other/package.json");
+
+        record(&history_file, app, "build").unwrap();
+        record(&history_file, app, "test").unwrap();
+        record(&history_file, other, "lint").unwrap();
+        let mut file = fs::File::options()
+            .append(true)
+            .open(&history_file)
+            .unwrap();
+        writeln!(file, "not json").unwrap();
+        writeln!(
+            file,
+            r#"{{"script":"old","package":"/work/app/package.json","timestamp":0}}"#
+        )
+        .unwrap();
+
+        let entries = read(&history_file).unwrap();
+        assert_eq!(entries.len(), 4);
+
+        let recent = scripts_run_since(&entries, app, Duration::from_secs(3600));
+        assert_eq!(
+            recent,
+            HashSet::from(["build".to_string(), "test".to_string()])
+        );
+    }
+}
@@ -0,0 +1,48 @@
+use std::io::{self, Write};
+
+/// Passes writes through with `prefix` added after every newline, so that
+/// something serialized straight into it comes out indented line by line
+pub struct IndentWriter<'a, W> {
+    inner: W,
+    prefix: &'a str,
+}
+
+impl<'a, W: Write> IndentWriter<'a, W> {
+    pub fn new(inner: W, prefix: &'a str) -> Self {
+        IndentWriter { inner, prefix }
+    }
+}
+
+impl<W: Write> Write for IndentWriter<'_, W> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut lines = buf.split(|&b| b == b'\n');
+        if let Some(first) = lines.next() {
+            self.inner.write_all(first)?;
+        }
+        for line in lines {
+            self.inner.write_all(b"\n")?;
+            self.inner.write_all(self.prefix.as_bytes())?;
+            self.inner.write_all(line)?;
+        }
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_indent_writer() {
+        let mut out = Vec::new();
+        let mut writer = IndentWriter::new(&mut out, "   ");
+        writer.write_all(b"{\n  \"a\"").unwrap();
+        writer.write_all(b": 1\n").unwrap();
+        writer.write_all(b"}").unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "{\n     \"a\": 1\n   }");
+    }
+}
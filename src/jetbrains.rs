@@ -0,0 +1,174 @@
+use anyhow::{bail, Context, Result};
+use quick_xml::Writer;
+use std::collections::HashMap;
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+
+const NPM_CONFIGURATION: &str = "js.build_tools.npm";
+
+/// Where IntelliJ IDEA and WebStorm look for the shared run configurations
+/// of the project in `dir`
+pub fn run_configurations_dir(dir: &Path) -> PathBuf {
+    dir.join(".idea").join("runConfigurations")
+}
+
+/// A run configuration file with an npm configuration per script, wrapped
+/// in the `ProjectRunConfigurationManager` component. The package.json is
+/// taken to be at the root of the project.
+pub fn run_configurations(names: &[&str]) -> io::Result<String> {
+    let mut writer = Writer::new_with_indent(Vec::new(), b' ', 2);
+    writer
+        .create_element("component")
+        .with_attribute(("name", "ProjectRunConfigurationManager"))
+        .write_inner_content(|writer| {
+            for name in names {
+                write_configuration(writer, name)?;
+            }
+            Ok(())
+        })?;
+    let mut xml = String::from_utf8(writer.into_inner()).expect("quick-xml writes UTF-8");
+    xml.push('\n');
+    Ok(xml)
+}
+
+/// Write a run configuration file per script to `run_configurations_dir`,
+/// replacing any with the same name. Returns how many were written; fails
+/// before writing anything if two scripts would share a file.
+pub fn export_run_configurations(dir: &Path, names: &[&str]) -> Result<usize> {
+    let mut taken = HashMap::new();
+    for name in names {
+        if let Some(other) = taken.insert(file_name(name), name) {
+            bail!(
+                "`{}` and `{}` would both be saved as {}",
+                other,
+                name,
+                file_name(name)
+            );
+        }
+    }
+    let out_dir = run_configurations_dir(dir);
+    fs::create_dir_all(&out_dir)
+        .with_context(|| format!("Failed to create {}", out_dir.display()))?;
+    for name in names {
+        let path = out_dir.join(file_name(name));
+        fs::write(&path, run_configurations(&[name])?)
+            .with_context(|| format!("Failed to write {}", path.display()))?;
+    }
+    Ok(names.len())
+}
+
+fn write_configuration(writer: &mut Writer<Vec<u8>>, name: &str) -> io::Result<()> {
+    writer
+        .create_element("configuration")
+        .with_attributes([
+            ("default", "false"),
+            ("name", name),
+            ("type", NPM_CONFIGURATION),
+            ("nameIsGenerated", "true"),
+        ])
+        .write_inner_content(|writer| {
+            writer
+                .create_element("package-json")
+                .with_attribute(("value", "$PROJECT_DIR$/package.json"))
+                .write_empty()?;
+            writer
+                .create_element("command")
+                .with_attribute(("value", "run"))
+                .write_empty()?;
+            writer
+                .create_element("scripts")
+                .write_inner_content(|writer| {
+                    writer
+                        .create_element("script")
+                        .with_attribute(("value", name))
+                        .write_empty()?;
+                    Ok(())
+                })?;
+            writer
+                .create_element("node-interpreter")
+                .with_attribute(("value", "project"))
+                .write_empty()?;
+            writer.create_element("envs").write_empty()?;
+            Ok(())
+        })?;
+    Ok(())
+}
+
+// The IDE's own naming: `build:css` is saved as build_css.xml
+fn file_name(name: &str) -> String {
+    let stem: String = name
+        .chars()
+        .map(|c| if c.is_alphanumeric() { c } else { '_' })
+        .collect();
+    format!("{}.xml", stem)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use quick_xml::events::Event;
+    use quick_xml::Reader;
+
+    // The `type` of every configuration, checking the XML is well-formed on
+    // the way
+    fn configuration_types(xml: &str) -> Vec<String> {
+        let mut reader = Reader::from_str(xml);
+        reader.config_mut().check_end_names = true;
+        let mut types = Vec::new();
+        loop {
+            match reader.read_event().unwrap() {
+                Event::Start(tag) if tag.name().as_ref() == "configuration" => {
+                    let kind = tag.try_get_attribute("type").unwrap().unwrap();
+                    types.push(kind.value.to_string());
+                }
+                Event::Eof => break,
+                _ => {}
+            }
+        }
+        types
+    }
+
+    #[test]
+    fn test_run_configurations() {
+        let xml = run_configurations(&["build", "test:<unit>"]).unwrap();
+        assert!(xml.starts_with("<component name=\"ProjectRunConfigurationManager\">\n"));
+        assert!(xml.contains(
+            "  <configuration default=\"false\" name=\"build\" type=\"js.build_tools.npm\" nameIsGenerated=\"true\">\n\
+             \x20   <package-json value=\"$PROJECT_DIR$/package.json\"/>\n\
+             \x20   <command value=\"run\"/>\n\
+             \x20   <scripts>\n\
+             \x20     <script value=\"build\"/>\n\
+             \x20   </scripts>\n"
+        ));
+        assert!(xml.contains("<script value=\"test:&lt;unit&gt;\"/>"));
+        assert_eq!(
+            configuration_types(&xml),
+            [NPM_CONFIGURATION, NPM_CONFIGURATION]
+        );
+    }
+
+    #[test]
+    fn test_export_run_configurations() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(
+            export_run_configurations(dir.path(), &["build:css", "lint"]).unwrap(),
+            2
+        );
+
+        let path = run_configurations_dir(dir.path()).join("build_css.xml");
+        let xml = fs::read_to_string(path).unwrap();
+        assert_eq!(configuration_types(&xml), [NPM_CONFIGURATION]);
+        assert!(run_configurations_dir(dir.path())
+            .join("lint.xml")
+            .is_file());
+
+        let other = tempfile::tempdir().unwrap();
+        let error = export_run_configurations(other.path(), &["build:css", "build_css"]);
+        assert_eq!(
+            error.unwrap_err().to_string(),
+            "`build:css` and `build_css` would both be saved as build_css.xml"
+        );
+        assert!(!run_configurations_dir(other.path()).exists());
+    }
+}
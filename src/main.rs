@@ -1,184 +1,2919 @@
-use anyhow::{Context, Result};
-use clap::Parser;
-use colored::*;
+use anyhow::{bail, Context, Result};
+use clap::parser::ValueSource;
+use clap::{CommandFactory, FromArgMatches, Parser, ValueEnum};
+use indexmap::IndexMap;
+use num_format::{SystemLocale, ToFormattedString};
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::env;
 use std::fs;
-use std::path::PathBuf;
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::time::Duration;
+
+use changes::ScriptDiff;
+use color::*;
+use encoding::OutputEncoding;
+use sort::SortBy;
+use table::TableStyle;
+use workspaces::Empty;
+
+mod ascii;
+mod bazel;
+mod bins;
+mod changes;
+mod checks;
+mod clipboard;
+mod color;
+mod config;
+mod destructive;
+mod encoding;
+mod engines;
+mod envs;
+mod formats;
+mod git;
+mod graph;
+mod history;
+mod indent;
+mod jetbrains;
+mod npmrc;
+mod package_manager;
+mod pager;
+mod picker;
+mod run;
+mod shell;
+mod sort;
+mod summary;
+mod table;
+mod tee;
+mod vscode;
+mod watch;
+mod workspaces;
 
 #[derive(Parser)]
 #[command(name = "script-list")]
 #[command(about = "📜 List npm scripts from package.json")]
 #[command(version = "0.1.0")]
 struct Cli {
-    /// Path to package.json (default: ./package.json)
+    /// Path to package.json (default: ./package.json); repeat it with
+    /// --list-only-changed to compare several
     #[arg(short, long, value_name = "PATH")]
-    path: Option<PathBuf>,
+    path: Vec<PathBuf>,
+
+    /// With several --path values, show only the scripts that differ between
+    /// the files or are missing from some of them
+    #[arg(long)]
+    list_only_changed: bool,
 
     /// Show only script names without descriptions
     #[arg(short, long)]
     names_only: bool,
 
+    /// With --format list, number the scripts (`1. build: tsc`), for
+    /// `--run <N>` with the same filters and sort
+    #[arg(long)]
+    numbered: bool,
+
+    /// Add a column with how to run each script with the project's package
+    /// manager (`npm run build`, `pnpm run build`...)
+    #[arg(long, visible_alias = "with-invocation")]
+    print_pm_command: bool,
+
+    /// Show only the commands, one per line, without script names
+    #[arg(long, conflicts_with = "names_only")]
+    commands_only: bool,
+
+    /// Mark scripts that look like they expect arguments ($1, $@, trailing --)
+    #[arg(long)]
+    mark_runnable: bool,
+
+    /// List the executables in node_modules/.bin below the table
+    #[arg(long)]
+    show_node_modules_bin: bool,
+
+    /// Mark scripts added (+) or changed (~) since the last listing with this
+    /// flag, and list the removed ones
+    #[arg(long)]
+    highlight_changed: bool,
+
+    /// Compare with another package.json, like the base branch's: mark the
+    /// scripts added or changed since, like --highlight-changed, and list the
+    /// removed ones (with --format github-pr-comment, list only those)
+    #[arg(long, value_name = "PATH", conflicts_with = "highlight_changed")]
+    diff: Option<PathBuf>,
+
+    /// Add a column describing commands that match known patterns (extend
+    /// them with "summaries" in .scriptrc.json)
+    #[arg(long)]
+    summarize: bool,
+
+    /// Truncate script names longer than N characters
+    #[arg(long, value_name = "N")]
+    truncate_names: Option<usize>,
+
+    /// Truncate commands so lines fit in N columns (0 for no limit)
+    #[arg(long, value_name = "N")]
+    max_width: Option<usize>,
+
+    /// Never truncate names or commands, overriding --max-width and --truncate-names
+    #[arg(long)]
+    no_truncate: bool,
+
+    /// Syntax-highlight commands: keywords, strings, operators, variables and
+    /// the programs they run
+    #[arg(long)]
+    color_command: bool,
+
+    /// Dim the `npm run` (`yarn`, `pnpm run`...) that wrapper scripts start
+    /// with, so the script they run stands out; without colors it's left out
+    #[arg(long)]
+    strip_run_prefix: bool,
+
+    /// Show a short hash of each command, to spot changed scripts by diffing
+    /// two listings
+    #[arg(long)]
+    show_command_hash: bool,
+
+    /// What to end truncated names and commands with
+    #[arg(long, default_value = "…", value_name = "STR")]
+    truncate_marker: String,
+
     /// Filter scripts by name (case-insensitive)
     #[arg(short = 'f', long, value_name = "PATTERN")]
     filter: Option<String>,
 
+    /// Filter scripts by command (case-insensitive), combined with --filter
+    #[arg(long, value_name = "PATTERN")]
+    filter_by_command: Option<String>,
+
+    /// Scripts for --format devcontainer-features to run, by name
+    /// (case-insensitive; default: all those listed)
+    #[arg(long, value_name = "PATTERN")]
+    post_create_filter: Option<String>,
+
+    /// Show scripts whose name or command contains PATTERN (case-insensitive),
+    /// highlighting where it matched
+    #[arg(long, value_name = "PATTERN")]
+    search: Option<String>,
+
+    /// Print just the full command of the named script
+    #[arg(long, value_name = "NAME")]
+    script: Option<String>,
+
+    /// Only show scripts run with --run within DURATION (e.g. 30m, 12h, 7d)
+    #[arg(long, value_name = "DURATION", value_parser = history::parse_duration)]
+    since: Option<Duration>,
+
+    /// Hide scripts whose command is longer than N characters (default 80)
+    #[arg(long, value_name = "N", num_args = 0..=1, default_missing_value = "80")]
+    hide_long_commands: Option<usize>,
+
+    /// Hide scripts whose name starts with PREFIX (repeatable)
+    #[arg(long, value_name = "PREFIX")]
+    omit_prefixed: Vec<String>,
+
+    /// Show how each script's command differs between two .env files
+    #[arg(long, num_args = 2, value_names = ["ENV1", "ENV2"])]
+    compare_envs: Vec<PathBuf>,
+
+    /// Lay the scripts of another package.json over this one's (its commands
+    /// win), showing what each overridden script ran before
+    #[arg(long, value_name = "FILE")]
+    merge: Option<PathBuf>,
+
+    /// List the scripts of every workspace package instead of the root's
+    /// (the default for a plain listing in a monorepo root)
+    #[arg(long)]
+    workspaces: bool,
+
+    /// In a monorepo root, list the root's own scripts rather than switching
+    /// to --workspaces
+    #[arg(long, conflicts_with = "workspaces")]
+    no_auto_workspaces: bool,
+
+    /// With --workspaces, what to do with packages that have no scripts
+    #[arg(
+        long,
+        value_enum,
+        default_value = "skip",
+        value_name = "MODE",
+        requires = "workspaces"
+    )]
+    empty: Empty,
+
+    /// With --workspaces, leave out packages marked `"private": true`
+    #[arg(long, visible_alias = "no-private", requires = "workspaces")]
+    skip_private: bool,
+
     /// Output format
     #[arg(short = 'F', long, value_enum, default_value = "table")]
     format: OutputFormat,
+
+    /// List the output formats with what each one is for, then exit
+    #[arg(long)]
+    list_formats: bool,
+
+    /// Border around the table format's rows
+    #[arg(long, value_enum, default_value = "none", value_name = "STYLE")]
+    table_style: TableStyle,
+
+    /// Scripts to tick in the checklist format
+    #[arg(long, value_delimiter = ',', value_name = "NAMES")]
+    checked: Vec<String>,
+
+    /// Fields to include per script in JSON output (default: a flat name → command map)
+    #[arg(long, value_enum, value_delimiter = ',', value_name = "FIELDS")]
+    fields: Vec<Field>,
+
+    /// package.json key holding the script descriptions (default: `scripts-info`,
+    /// or `scriptInfoKey` from .scriptrc.json)
+    #[arg(long, value_name = "NAME")]
+    script_info_key: Option<String>,
+
+    /// Sort order; every order but `none` breaks ties by name (`source`
+    /// re-reads package.json to recover the written order, `priority` takes
+    /// it from `order` in .scriptrc.json)
+    #[arg(long, value_enum, default_value = "name")]
+    sort_by: SortBy,
+
+    /// Keep the scripts in package.json order (the same as --sort-by none)
+    #[arg(long, conflicts_with = "sort_by")]
+    no_sort: bool,
+
+    /// Pick a script to run by typing part of its name
+    #[arg(short, long, conflicts_with_all = ["run", "watch"])]
+    interactive: bool,
+
+    /// Run a script through the project's package manager; a number runs
+    /// the script listed at that place (see --numbered)
+    #[arg(long, value_name = "NAME")]
+    run: Option<String>,
+
+    /// Show what --run would execute without running it
+    #[arg(long, requires = "run")]
+    dry_run: bool,
+
+    /// Print just the shell command --run would execute, env prefixes and
+    /// all, for `CMD=$(sl --run build --print-command)`; --pre-run,
+    /// --post-run and --timeout aren't part of it
+    #[arg(long, requires = "run", conflicts_with = "dry_run")]
+    print_command: bool,
+
+    /// Shell to run the --run script with, instead of the platform default or
+    /// .npmrc's script-shell (e.g. bash, or cmd / powershell on Windows).
+    /// npm only: the other package managers have no such setting to pass on.
+    #[arg(
+        long,
+        value_name = "SHELL",
+        requires = "run",
+        visible_alias = "run-script-shell"
+    )]
+    shell: Option<String>,
+
+    /// Show the steps of the --run script one per line and ask before running
+    #[arg(long, requires = "run")]
+    preview: bool,
+
+    /// Shell command to run (with `sh -c`) before the --run script; the script
+    /// is skipped if it fails
+    #[arg(long, value_name = "COMMAND", requires = "run")]
+    pre_run: Option<String>,
+
+    /// Shell command to run (with `sh -c`) after the --run script, whether or
+    /// not it succeeded
+    #[arg(long, value_name = "COMMAND", requires = "run")]
+    post_run: Option<String>,
+
+    /// Set a variable in the --run script's environment; repeat it for more
+    #[arg(long, value_name = "KEY=VAL", value_parser = parse_env_var, requires = "run")]
+    env: Vec<(String, String)>,
+
+    /// Load variables from a .env file into the --run script's environment;
+    /// repeat it to load several, later files overriding earlier ones
+    #[arg(long, value_name = "PATH", requires = "run")]
+    env_file: Vec<PathBuf>,
+
+    /// Directory to run the --run script (and --pre-run / --post-run) in,
+    /// instead of the package's
+    #[arg(long, value_name = "DIR", requires = "run")]
+    cwd: Option<PathBuf>,
+
+    /// Kill the --run script (and everything it started) if it's still going
+    /// after SECONDS, and exit with 124
+    #[arg(long, value_name = "SECONDS", requires = "run")]
+    timeout: Option<u64>,
+
+    /// Show a desktop notification when the --run script finishes, saying
+    /// whether it succeeded (nothing happens where notifications aren't
+    /// supported)
+    #[arg(long, requires = "run")]
+    notify: bool,
+
+    /// Don't ask for confirmation, with --preview or before a script that
+    /// looks destructive (clean, reset, rm -rf...)
+    #[arg(short, long)]
+    yes: bool,
+
+    /// Arguments passed on to the script run with --run
+    #[arg(last = true, value_name = "ARGS")]
+    args: Vec<String>,
+
+    /// When to color the output
+    #[arg(long, value_enum, default_value = "auto", value_name = "WHEN")]
+    color: ColorChoice,
+
+    /// Plain output: no colors and no locale-specific number formatting
+    #[arg(long)]
+    plain: bool,
+
+    /// Character encoding to write the output in
+    #[arg(long, value_enum, default_value = "utf-8", value_name = "ENCODING")]
+    output_encoding: OutputEncoding,
+
+    /// Replace anything outside ASCII in the output (names, commands, borders
+    /// and symbols) with an ASCII stand-in, or `?`
+    #[arg(long)]
+    ascii_only: bool,
+
+    /// Write the listing to FILE instead of stdout
+    #[arg(short, long, value_name = "FILE")]
+    output: Option<PathBuf>,
+
+    /// Write the listing to PATH as well as to stdout, exactly as printed
+    #[arg(long, value_name = "PATH", conflicts_with_all = ["output", "pager"])]
+    tee: Option<PathBuf>,
+
+    /// Page output that doesn't fit the terminal through $PAGER, or a built-in
+    /// pager when it isn't set
+    #[arg(long, conflicts_with = "watch")]
+    pager: bool,
+
+    /// Keep colors when --output is a regular file, even though escape codes
+    /// end up in it
+    #[arg(long, requires = "output")]
+    force_color_file: bool,
+
+    /// Print a Graphviz DOT graph of which scripts run which (pipe into `dot -Tpng`)
+    #[arg(long)]
+    graph_dot: bool,
+
+    /// Add a task per script to .vscode/tasks.json, keeping the tasks already there
+    #[arg(long)]
+    export_vscode_tasks: bool,
+
+    /// With --format jetbrains-run, write a file per script to
+    /// .idea/runConfigurations instead of printing them all
+    #[arg(long)]
+    split_files: bool,
+
+    /// With --format bazel, write the wrapper scripts the rules run to DIR
+    /// instead of printing them to stderr. DIR is relative to the package's
+    /// directory, which is where the BUILD file is taken to go.
+    #[arg(long, value_name = "DIR")]
+    wrapper_dir: Option<PathBuf>,
+
+    /// Also copy the output to the system clipboard; on Linux a background
+    /// sl keeps it there until something else is copied
+    #[arg(long)]
+    copy: bool,
+
+    /// Re-render whenever package.json changes
+    #[arg(short, long)]
+    watch: bool,
+
+    /// Re-run the scripts matching --filter (default "test") whenever files in
+    /// the project change
+    #[arg(long, conflicts_with_all = ["watch", "run", "interactive"])]
+    only_changed: bool,
+
+    /// With --watch, whether to clear the screen before each re-render; with
+    /// `off`, renders scroll by under a timestamp each
+    #[arg(
+        long,
+        value_enum,
+        default_value = "on",
+        value_name = "WHEN",
+        requires = "watch"
+    )]
+    watch_clear: watch::Clear,
+
+    /// With --watch, show only the scripts added, changed or removed since
+    /// the previous render (the first one still shows them all)
+    #[arg(long, requires = "watch")]
+    watch_diff: bool,
+
+    /// With --watch or --only-changed, poll for changes every MS milliseconds
+    /// instead of using native file events (for network filesystems)
+    #[arg(long, value_name = "MS", value_parser = clap::value_parser!(u64).range(1..))]
+    watch_interval: Option<u64>,
+
+    /// Warn if the node in PATH doesn't satisfy the engines recorded in the lockfile
+    #[arg(long)]
+    from_package_lock: bool,
+
+    /// Warn if package.json is newer than the lockfile
+    #[arg(long)]
+    check_lock: bool,
+
+    /// Warn about scripts running local files that lack a shebang or aren't executable
+    #[arg(long, visible_alias = "check-paths")]
+    check_shebang: bool,
+
+    /// Warn about scripts using Unix-only programs or shell syntax that
+    /// Windows cmd can't run (rm, export, NAME=value prefixes, $(...))
+    #[arg(long, visible_alias = "annotate-os")]
+    check_portability: bool,
+
+    /// Warn about script names that `npm run` can't take as a plain word
+    /// (also part of --validate)
+    #[arg(long)]
+    validate_names: bool,
+
+    /// Warn if HEAD is tagged with a version other than package.json's
+    #[arg(long)]
+    version_bump_check: bool,
+
+    /// Warn about scripts whose programs aren't in node_modules/.bin or PATH
+    #[arg(long)]
+    validate: bool,
+
+    /// Exit with an error if any warnings were printed
+    #[arg(long)]
+    strict: bool,
+
+    /// Fail, naming the missing ones, unless package.json defines all these
+    /// scripts (with --workspaces, every member has to)
+    #[arg(
+        long,
+        value_delimiter = ',',
+        value_name = "NAMES",
+        visible_alias = "compat-check"
+    )]
+    require: Vec<String>,
+
+    /// The arguments given on the command line rather than left at their
+    /// defaults, by id
+    #[arg(skip)]
+    given: Vec<String>,
+}
+
+/// The arguments a monorepo root's automatic `--workspaces` listing honours;
+/// any other one keeps sl on the root's own scripts
+const AUTO_WORKSPACES_ARGS: &[&str] = &[
+    "path",
+    "format",
+    "filter",
+    "filter_by_command",
+    "search",
+    "omit_prefixed",
+    "sort_by",
+    "no_sort",
+    "require",
+    "truncate_names",
+    "max_width",
+    "no_truncate",
+    "truncate_marker",
+    "strip_run_prefix",
+    "color_command",
+    "table_style",
+    "color",
+    "plain",
+    "output_encoding",
+    "ascii_only",
+    "output",
+    "tee",
+    "pager",
+    "from_package_lock",
+    "check_lock",
+    "check_shebang",
+    "check_portability",
+    "validate_names",
+    "version_bump_check",
+    "validate",
+    "strict",
+];
+
+/// Parse `args` like `Cli::parse_from`, also noting which were given
+fn parse_cli<I, T>(args: I) -> Cli
+where
+    I: IntoIterator<Item = T>,
+    T: Into<std::ffi::OsString> + Clone,
+{
+    let command = Cli::command();
+    let matches = command.clone().get_matches_from(args);
+    let mut cli = Cli::from_arg_matches(&matches).unwrap_or_else(|e| e.exit());
+    // The arguments only, not the group clap makes of them all
+    cli.given = command
+        .get_arguments()
+        .map(|arg| arg.get_id().as_str())
+        .filter(|id| {
+            matches!(
+                matches.value_source(id),
+                Some(ValueSource::CommandLine | ValueSource::EnvVariable)
+            )
+        })
+        .map(str::to_string)
+        .collect();
+    cli
 }
 
 #[derive(Clone, Copy, Debug, clap::ValueEnum)]
 enum OutputFormat {
+    /// Aligned names and commands under the package name
     Table,
+    /// `name: command` lines
     List,
+    /// A name → command object (see --fields for more per script)
     Json,
+    /// A `strategy.matrix` block listing the scripts, for GitHub Actions
+    GithubActionsMatrix,
+    /// An Azure Pipelines `steps:` list running each script
+    AzurePipelines,
+    /// A CircleCI `steps:` list with a `run:` step per script
+    Circleci,
+    /// A turbo.json template with a pipeline task per script
+    TurboPipeline,
+    /// An Nx project.json with a target per script
+    NxProject,
+    /// A `[tool.poe.tasks]` section of pyproject.toml, for poethepoet
+    PoeTasks,
+    /// Gradle `Exec` tasks running each script, for build.gradle
+    GradleTasks,
+    /// Rake tasks running each script, `build:prod` in `namespace :build`
+    RakeTasks,
+    /// A justfile with a recipe per script, for the `just` command runner
+    Justfile,
+    /// JSON Schema whose enum is the set of script names
+    JsonSchema,
+    /// Just the command of the --script script, safe to eval
+    PlainCommand,
+    /// `export` lines for each script's env prefix, then the command
+    Environment,
+    /// Commented `RUN npm run <name>` lines for a Dockerfile
+    DockerfileComments,
+    /// JSON with runner, lifecycle hooks and env vars per script, for IDE plugins
+    CompletionsJson,
+    /// Jira wiki markup table
+    JiraWiki,
+    /// A Markdown comment body for a PR bot, with the scripts in a folded
+    /// table (only the changed ones with --diff)
+    GithubPrComment,
+    /// Tree of which scripts run which via `npm run` and friends
+    DependencyGraph,
+    /// Graphviz DOT graph of which scripts run which, like --graph-dot
+    Dot,
+    /// GitHub Markdown task list, one unchecked item per script
+    Checklist,
+    /// `name  # command` lines, like a requirements.txt, for grepping
+    RequirementsTxt,
+    /// A Nix attribute set of the scripts, for flakes
+    Nix,
+    /// A Bash completion function for `npm run <TAB>` in the current project
+    CompletionsBash,
+    /// Bare script names, one per line, for completion functions to call
+    CompletionHelper,
+    /// GNU Make assignments, `BUILD_CMD = webpack`, to include in a Makefile
+    EnvMake,
+    /// A complete .vscode/tasks.json with an npm task per script
+    VscodeTasks,
+    /// A devcontainer.json `postCreateCommand` running the scripts picked
+    /// by --post-create-filter
+    DevcontainerFeatures,
+    /// OpenTelemetry span attributes (`npm.script.name`...) as JSON
+    OpentelemetryAttributes,
+    /// JetBrains IDE npm run configurations (XML); see --split-files
+    JetbrainsRun,
+    /// Bazel BUILD `sh_binary` rules wrapping each script; see --wrapper-dir
+    Bazel,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum ColorChoice {
+    /// Color unless NO_COLOR is set or the output goes to a file
+    Auto,
+    Always,
+    Never,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+enum Field {
+    Name,
+    Command,
+    /// From the `scripts-info` key (see --script-info-key)
+    Description,
+    /// Other scripts this one runs via `npm run` and friends
+    References,
+    /// The absolute path of the package.json, with `/` separators on every
+    /// platform
+    Path,
+    /// The package manager its lockfile points to (npm without one)
+    PackageManager,
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Default, Deserialize)]
 struct PackageJson {
     #[serde(default)]
-    scripts: HashMap<String, String>,
+    name: Option<String>,
+    #[serde(default)]
+    version: Option<String>,
+    // In the order package.json lists them, for --no-sort
+    #[serde(default)]
+    scripts: IndexMap<String, String>,
+    #[serde(default)]
+    engines: HashMap<String, String>,
+    #[serde(default, rename = "scripts-info")]
+    scripts_info: HashMap<String, String>,
+    #[serde(default)]
+    workspaces: Option<workspaces::Workspaces>,
+    #[serde(default)]
+    private: bool,
+    // The other keys, for --script-info-key to take descriptions from
+    #[serde(flatten)]
+    other: serde_json::Map<String, serde_json::Value>,
+}
+
+// `--env KEY=VAL`
+fn parse_env_var(arg: &str) -> Result<(String, String), String> {
+    match arg.split_once('=') {
+        Some((name, value)) if shell::is_assignment(arg) => {
+            Ok((name.to_string(), value.to_string()))
+        }
+        _ => Err(format!("expected KEY=VAL, got `{}`", arg)),
+    }
 }
 
 fn main() -> Result<()> {
-    let cli = Cli::parse();
+    #[cfg(all(
+        unix,
+        not(any(target_os = "macos", target_os = "android", target_os = "emscripten"))
+    ))]
+    if env::args_os().nth(1).as_deref() == Some(clipboard::HELPER_ARG.as_ref()) {
+        return clipboard::serve_from_stdin();
+    }
+    let cli = parse_cli(env::args_os());
+    if let Some(enabled) = color_override(&cli) {
+        control::set_override(enabled);
+    }
+    if cli.list_formats {
+        return write_output(&cli, list_formats().as_bytes());
+    }
+
+    if cli.split_files && !matches!(cli.format, OutputFormat::JetbrainsRun) {
+        bail!("--split-files only applies to --format jetbrains-run");
+    }
+    if cli.numbered && !matches!(cli.format, OutputFormat::List) {
+        bail!("--numbered only applies to --format list");
+    }
+    if cli.wrapper_dir.is_some() && !matches!(cli.format, OutputFormat::Bazel) {
+        bail!("--wrapper-dir only applies to --format bazel");
+    }
+    if cli.watch_interval.is_some() && !cli.watch && !cli.only_changed {
+        bail!("--watch-interval only applies to --watch and --only-changed");
+    }
+    if let Some(dir) = &cli.wrapper_dir {
+        // It's the rules' `srcs` too, which have to stay inside the package
+        let inside = dir
+            .components()
+            .all(|part| matches!(part, Component::Normal(_) | Component::CurDir));
+        if !inside {
+            bail!("--wrapper-dir must be a relative path inside the package, without `..`");
+        }
+    }
 
+    if cli.list_only_changed {
+        return list_only_changed(&cli);
+    }
+    if cli.path.len() > 1 {
+        bail!("Several --path values can only be compared with --list-only-changed");
+    }
     let package_path = cli
         .path
+        .first()
+        .cloned()
         .unwrap_or_else(|| PathBuf::from("package.json"));
 
-    let package = read_package_json(&package_path)?;
-
-    if package.scripts.is_empty() {
-        println!("{}", "⚠️  No scripts found in package.json".yellow());
-        return Ok(());
+    if let Some(script) = &cli.run {
+        let package = read_package_json(&package_path)?;
+        let script = run_target(&cli, &package_path, &package, script)?;
+        return exit_like_script(run::run_script(&cli, &package_path, &package, &script));
     }
 
-    let mut scripts: Vec<_> = package.scripts.clone().into_iter().collect();
-
-    // Filter if specified
-    if let Some(pattern) = cli.filter {
-        let pattern = pattern.to_lowercase();
-        scripts.retain(|(name, _)| name.to_lowercase().contains(&pattern));
+    if cli.only_changed {
+        let package = read_package_json(&package_path)?;
+        return rerun_on_change(&cli, &package_path, &package);
     }
 
-    // Sort by name
-    scripts.sort_by(|a, b| a.0.cmp(&b.0));
-
-    // Output scripts
-    match cli.format {
-        OutputFormat::Table => print_scripts(&package, &scripts, cli.names_only),
-        OutputFormat::List => print_list(&scripts, cli.names_only),
-        OutputFormat::Json => print_json(&scripts)?,
+    if cli.watch {
+        let kind = watch::watcher_kind(cli.watch_interval);
+        let mut previous = None;
+        return watch::watch(&package_path, kind, cli.watch_clear, || {
+            if cli.watch_diff {
+                // Only what the listing itself would show
+                let (package, _) = read_listed_package(&cli, &package_path)?;
+                let scripts = package.scripts.into_iter().collect();
+                let (scripts, _) = listed_scripts(&cli, &package_path, scripts)?;
+                if let Some(previous) = previous.replace(scripts.clone()) {
+                    let mut out = Vec::new();
+                    changes::print_render_diff(&mut out, &scripts, &previous)?;
+                    return write_output(&cli, &out);
+                }
+            }
+            list_scripts(&cli, &package_path).map(|_| ())
+        });
     }
 
-    Ok(())
+    // --interactive runs the picked script from in here
+    let warnings = exit_like_script(list_scripts(&cli, &package_path))?;
+    exit_for_warnings(&cli, &warnings)
 }
 
-fn read_package_json(path: &PathBuf) -> Result<PackageJson> {
-    let content = match fs::read_to_string(path) {
-        Ok(c) => c,
-        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
-            // Get current directory name
-            let current_dir = env::current_dir().ok();
-            let dir_name = current_dir
-                .as_ref()
-                .and_then(|p| p.file_name())
-                .and_then(|n| n.to_str())
-                .unwrap_or("unknown");
-            
-            eprintln!();
-            eprintln!("   {}", dir_name.red().bold());
-            eprintln!();
-            eprintln!("   No package.json file found:");
-            eprintln!("     {}", current_dir.as_ref().map(|p| p.display().to_string()).unwrap_or_default());
-            eprintln!();
-            
-            std::process::exit(1);
+/// Exit the way the `--run` script did when that's what `result` failed
+/// with, so that CI sees its exit code rather than a generic 1
+fn exit_like_script<T>(result: Result<T>) -> Result<T> {
+    if let Err(e) = &result {
+        if let Some(code) = run::exit_code(e) {
+            eprintln!("Error: {:#}", e);
+            std::process::exit(code);
         }
-        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display()))?,
-    };
+    }
+    result
+}
 
-    let package: PackageJson = serde_json::from_str(&content)
-        .with_context(|| format!("Failed to parse {} as JSON", path.display()))?;
+/// `--only-changed`: a focused `--watch --run` that re-runs the test scripts
+/// (or whichever `--filter` picks) when project files change
+fn rerun_on_change(cli: &Cli, package_path: &Path, package: &PackageJson) -> Result<()> {
+    let mut scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+    let pattern = cli.filter.as_deref().unwrap_or("test").to_lowercase();
+    scripts.retain(|(name, _)| name.to_lowercase().contains(&pattern));
+    if let Some(pattern) = &cli.filter_by_command {
+        filter_by_command(&mut scripts, pattern);
+    }
+    omit_prefixed(&mut scripts, &cli.omit_prefixed);
+    scripts.sort();
+    if scripts.is_empty() {
+        bail!("No scripts matching `{}` to re-run", pattern);
+    }
 
-    Ok(package)
+    let names: Vec<_> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+    eprintln!(
+        "{}",
+        format!("Watching for changes to re-run {}", names.join(", ")).green()
+    );
+    // Absolute, to match the paths in file events
+    let dir = package_dir(package_path);
+    let dir = dir.canonicalize().unwrap_or(dir);
+    let kind = watch::watcher_kind(cli.watch_interval);
+    watch::watch_files(&dir, kind, |changed| {
+        let time = chrono::Local::now().format("%H:%M:%S");
+        let first = changed[0].strip_prefix(&dir).unwrap_or(&changed[0]);
+        let what = match changed.len() {
+            1 => format!("{} changed", first.display()),
+            n => format!("{} and {} other file(s) changed", first.display(), n - 1),
+        };
+        eprintln!();
+        eprintln!("{}", format!("[{}] {}", time, what).dimmed());
+        for name in &names {
+            run::run_script(cli, package_path, package, name)?;
+        }
+        Ok(())
+    })
 }
 
-fn print_scripts(_package: &PackageJson, scripts: &[(String, String)], names_only: bool) {
-    if names_only {
-        for (name, _) in scripts {
-            println!("{}", name);
+/// Read, check, filter, sort and print the scripts, returning the warnings
+/// that were printed along the way
+fn list_scripts(cli: &Cli, package_path: &Path) -> Result<Vec<String>> {
+    if cli.workspaces {
+        return list_workspaces(cli, package_path, false);
+    }
+    if auto_workspaces(cli, package_path)? {
+        return list_workspaces(cli, package_path, true);
+    }
+
+    let (package, overridden) = read_listed_package(cli, package_path)?;
+    let mut warnings = collect_warnings(cli, package_path, &package)?;
+    for warning in &warnings {
+        print_warning(cli, warning);
+    }
+    if !cli.require.is_empty() {
+        let all_scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+        let missing = checks::missing_scripts(&all_scripts, &cli.require);
+        if !missing.is_empty() {
+            bail!(
+                "{} is missing required script(s): {}",
+                package_path.display(),
+                missing.join(", ")
+            );
         }
-        return;
     }
 
-    // Use current directory name (like rousan/sl does)
-    let module_name = env::current_dir()
-        .ok()
-        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
-        .unwrap_or_else(|| "unknown".to_string());
-    
-    // Print module name (3 spaces prefix, green)
-    println!();
-    println!("   {}", module_name.green());
-    println!();
+    // Completion functions call the helper and can't have a warning mixed in
+    let helper = matches!(cli.format, OutputFormat::CompletionHelper);
+    if package.scripts.is_empty() && !helper {
+        let message = format!("{}\n", "⚠️  No scripts found in package.json".yellow());
+        write_output(cli, message.as_bytes())?;
+        return Ok(warnings);
+    }
 
-    // Calculate max script name length for padding
-    let max_len = scripts.iter().map(|(name, _)| name.len()).max().unwrap_or(0);
+    let scripts: Vec<_> = package.scripts.clone().into_iter().collect();
 
-    // Print each script (3 spaces + " - " prefix)
-    for (name, command) in scripts {
-        let padded_name = format!("{:<width$}", name, width = max_len);
-        println!("    - {} : {}", padded_name.truecolor(128, 128, 128), command);
+    if let Some(name) = &cli.script {
+        let Some(command) = get_script(&scripts, name) else {
+            bail!("No script named `{}` in {}", name, package_path.display());
+        };
+        write_output(cli, formats::plain_command(command).as_bytes())?;
+        return Ok(warnings);
+    }
+    if let OutputFormat::PlainCommand = cli.format {
+        bail!("--format plain-command prints a single script and needs --script <NAME>");
     }
 
-    println!();
-}
+    let (scripts, hidden) = listed_scripts(cli, package_path, scripts)?;
 
-fn print_list(scripts: &[(String, String)], names_only: bool) {
-    println!();
-    for (name, command) in scripts {
-        if names_only {
-            println!("   {}", name);
-        } else {
-            println!("   {}: {}", name, command);
+    if cli.graph_dot {
+        let names: Vec<&str> = package.scripts.keys().map(String::as_str).collect();
+        write_output(cli, graph::dot(&scripts, &names).as_bytes())?;
+        return Ok(warnings);
+    }
+
+    if cli.export_vscode_tasks {
+        let dir = package_dir(package_path);
+        let manager = package_manager::PackageManager::detect(&dir);
+        let added = vscode::export_tasks(&dir, &scripts, manager)?;
+        let message = format!(
+            "Added {} task(s) to {}\n",
+            added,
+            vscode::tasks_file(&dir).display()
+        );
+        write_output(cli, message.as_bytes())?;
+        return Ok(warnings);
+    }
+
+    if cli.interactive {
+        if let Some(name) = picker::pick(&scripts)? {
+            run::run_script(cli, package_path, &package, &name)?;
+        }
+        return Ok(warnings);
+    }
+
+    let changes = if let Some(base_path) = &cli.diff {
+        let base: Vec<_> = read_package_json(base_path)?.scripts.into_iter().collect();
+        let scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+        Some(changes::diff_with_previous(&scripts, &base))
+    } else if cli.highlight_changed {
+        highlight_changes(package_path, &package)?
+    } else {
+        None
+    };
+    let summaries = if cli.summarize {
+        Some(summarize(package_path, &scripts)?)
+    } else {
+        None
+    };
+    let invocations = if cli.print_pm_command {
+        let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+        let invocations = scripts
+            .iter()
+            .map(|(name, _)| (name.clone(), manager.command_line(name)))
+            .collect();
+        Some(invocations)
+    } else {
+        None
+    };
+    let annotations = Annotations {
+        changes: changes.as_ref(),
+        summaries: summaries.as_ref(),
+        invocations: invocations.as_ref(),
+        overridden: overridden.as_ref(),
+    };
+
+    // Render into a buffer first so a closed pipe can't leave a partial line
+    let mut out = Vec::new();
+    if let [left, right] = cli.compare_envs.as_slice() {
+        let left = envs::EnvFile::load(left)?;
+        let right = envs::EnvFile::load(right)?;
+        envs::print_comparison(&mut out, &scripts, &left, &right)?;
+        write_output(cli, &out)?;
+        return Ok(warnings);
+    }
+    match cli.format {
+        OutputFormat::Table => {
+            print_scripts(&mut out, &package, &scripts, hidden, &annotations, cli)?;
+            if let Some(changes) = &changes {
+                print_removed(&mut out, &changes.removed)?;
+            }
+            if cli.show_node_modules_bin {
+                let bin_dir = package_dir(package_path).join("node_modules").join(".bin");
+                let all_scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+                print_binaries(&mut out, &bins::scan_bin_dir(&bin_dir), &all_scripts)?;
+            }
+        }
+        OutputFormat::List => print_list(&mut out, &scripts, cli)?,
+        OutputFormat::Json if !cli.fields.is_empty() => {
+            print_json_fields(&mut out, package_path, &package, &scripts, &cli.fields)?
+        }
+        OutputFormat::Json => print_json(&mut out, &scripts)?,
+        OutputFormat::GithubActionsMatrix => {
+            write!(out, "{}", formats::github_actions_matrix(&scripts)?)?
+        }
+        OutputFormat::AzurePipelines => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            write!(out, "{}", formats::azure_pipelines(&scripts, manager)?)?
+        }
+        OutputFormat::Circleci => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            write!(out, "{}", formats::circleci(&scripts, manager)?)?
+        }
+        OutputFormat::TurboPipeline => write!(out, "{}", formats::turbo_pipeline(&scripts)?)?,
+        OutputFormat::NxProject => {
+            let project = formats::nx_project(package.name.as_deref(), &scripts)?;
+            write!(out, "{}", project)?
+        }
+        OutputFormat::PoeTasks => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            write!(out, "{}", formats::poe_tasks(&scripts, manager))?
+        }
+        OutputFormat::GradleTasks => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            write!(out, "{}", formats::gradle_tasks(&scripts, manager))?
+        }
+        OutputFormat::RakeTasks => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            write!(out, "{}", formats::rake_tasks(&scripts, manager))?
+        }
+        OutputFormat::Justfile => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            write!(out, "{}", formats::justfile(&scripts, manager))?
+        }
+        OutputFormat::JsonSchema => write!(out, "{}", formats::json_schema(&scripts)?)?,
+        OutputFormat::Environment => write!(out, "{}", formats::environment(&scripts))?,
+        OutputFormat::JiraWiki => {
+            let table = formats::jira_wiki(package.name.as_deref(), &scripts);
+            write!(out, "{}", table)?
+        }
+        OutputFormat::GithubPrComment => {
+            let (name, version) = (package.name.as_deref(), package.version.as_deref());
+            let comment = formats::github_pr_comment(name, version, &scripts, changes.as_ref());
+            write!(out, "{}", comment)?
+        }
+        OutputFormat::CompletionsJson => {
+            let names: Vec<&str> = package.scripts.keys().map(String::as_str).collect();
+            write!(out, "{}", formats::completions_json(&scripts, &names)?)?
+        }
+        OutputFormat::DockerfileComments => {
+            let name = package.name.as_deref();
+            let comments = formats::dockerfile_comments(name, &scripts, &package.scripts_info);
+            write!(out, "{}", comments)?
+        }
+        OutputFormat::DependencyGraph => {
+            let graph = graph::build_script_graph(&scripts);
+            for cycle in graph::cycles(&graph) {
+                let warning = format!("Scripts run each other in a cycle: {}", cycle.join(" → "));
+                print_warning(cli, &warning);
+                warnings.push(warning);
+            }
+            write!(out, "{}", graph::tree(&graph))?
+        }
+        OutputFormat::Dot => {
+            let names: Vec<&str> = package.scripts.keys().map(String::as_str).collect();
+            write!(out, "{}", graph::dot(&scripts, &names))?
+        }
+        OutputFormat::Checklist => {
+            let items: Vec<_> = scripts
+                .iter()
+                .map(|(name, command)| {
+                    let item = format!("- [ ] {} — ``", name);
+                    let prefix_width = item.chars().count();
+                    (name.clone(), display_command(command, prefix_width, cli))
+                })
+                .collect();
+            write!(out, "{}", formats::checklist(&items, &cli.checked))?
+        }
+        OutputFormat::RequirementsTxt => write!(out, "{}", formats::requirements_txt(&scripts))?,
+        OutputFormat::Nix => write!(out, "{}", formats::nix(&scripts))?,
+        OutputFormat::CompletionsBash => write!(out, "{}", formats::completions_bash())?,
+        OutputFormat::CompletionHelper => write!(out, "{}", formats::completion_helper(&scripts))?,
+        OutputFormat::EnvMake => write!(out, "{}", formats::env_make(&scripts)?)?,
+        OutputFormat::JetbrainsRun if cli.split_files => {
+            let dir = package_dir(package_path);
+            let names: Vec<&str> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+            let written = jetbrains::export_run_configurations(&dir, &names)?;
+            let configurations_dir = jetbrains::run_configurations_dir(&dir);
+            let message = format!(
+                "Wrote {} run configuration(s) to {}\n",
+                written,
+                configurations_dir.display()
+            );
+            write!(out, "{}", message)?
+        }
+        OutputFormat::JetbrainsRun => {
+            let names: Vec<&str> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+            write!(out, "{}", jetbrains::run_configurations(&names)?)?
+        }
+        OutputFormat::Bazel => {
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            let names: Vec<&str> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+            bazel::check_target_names(&names)?;
+            let wrapper_dir = match &cli.wrapper_dir {
+                Some(dir) => {
+                    let out_dir = package_dir(package_path).join(dir);
+                    let written = bazel::export_wrappers(&out_dir, &names, manager)?;
+                    eprintln!(
+                        "Wrote {} wrapper script(s) to {}",
+                        written,
+                        out_dir.display()
+                    );
+                    dir.to_string_lossy().replace('\\', "/")
+                }
+                None => {
+                    for name in &names {
+                        let file = bazel::file_name(name);
+                        let wrapper = bazel::wrapper(name, manager);
+                        eprint!("# {}/{}\n{}\n", bazel::DEFAULT_WRAPPER_DIR, file, wrapper);
+                    }
+                    bazel::DEFAULT_WRAPPER_DIR.to_string()
+                }
+            };
+            write!(out, "{}", bazel::build_file(&names, &wrapper_dir))?
+        }
+        OutputFormat::DevcontainerFeatures => {
+            let pattern = cli.post_create_filter.as_deref().unwrap_or_default();
+            let pattern = pattern.to_lowercase();
+            let names: Vec<&str> = scripts
+                .iter()
+                .map(|(name, _)| name.as_str())
+                .filter(|name| name.to_lowercase().contains(&pattern))
+                .collect();
+            if names.is_empty() {
+                bail!("No scripts match --post-create-filter `{}`", pattern);
+            }
+            let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+            let snippet = formats::devcontainer_post_create(&names, manager)?;
+            write!(out, "{}", snippet)?
+        }
+        OutputFormat::OpentelemetryAttributes => {
+            let name = package.name.as_deref();
+            let attributes = formats::opentelemetry_attributes(name, &scripts)?;
+            write!(out, "{}", attributes)?
+        }
+        OutputFormat::VscodeTasks => {
+            let tasks = vscode::npm_tasks(&scripts);
+            writeln!(out, "{}", serde_json::to_string_pretty(&tasks)?)?
+        }
+        OutputFormat::PlainCommand => unreachable!("handled with --script above"),
+    }
+    write_output(cli, &out)?;
+
+    if cli.copy {
+        let copied = clipboard::SystemClipboard::new()
+            .and_then(|mut clipboard| clipboard::copy_output(&mut clipboard, &out));
+        if let Err(e) = copied {
+            print_warning(cli, &format!("Could not copy to clipboard: {}", e));
         }
     }
-    println!();
+
+    Ok(warnings)
 }
 
-fn print_json(scripts: &[(String, String)]) -> Result<()> {
-    let map: HashMap<&str, &str> = scripts
+/// `--list-formats`: each `--format` value with its description
+fn list_formats() -> String {
+    let formats: Vec<_> = OutputFormat::value_variants()
         .iter()
-        .map(|(k, v)| (k.as_str(), v.as_str()))
+        .filter_map(|format| format.to_possible_value())
         .collect();
-    
-    let json = serde_json::to_string_pretty(&map)?;
-    println!();
-    for line in json.lines() {
-        println!("   {}", line);
+    let width = formats
+        .iter()
+        .map(|format| format.get_name().len())
+        .max()
+        .unwrap_or(0);
+    let mut out = String::from("\n");
+    for format in &formats {
+        let help = format.get_help().unwrap_or_default();
+        out.push_str(&format!("   {:<width$}  {}\n", format.get_name(), help));
     }
-    println!();
-    Ok(())
+    out.push('\n');
+    out
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
+/// `--list-only-changed`: compare the (filtered) scripts of each `--path`
+fn list_only_changed(cli: &Cli) -> Result<()> {
+    if cli.path.len() < 2 {
+        bail!("--list-only-changed needs at least two --path values to compare");
+    }
+    let mut sets = Vec::new();
+    for path in &cli.path {
+        let mut scripts: Vec<_> = read_package_json(path)?.scripts.into_iter().collect();
+        apply_filters(cli, &mut scripts);
+        sets.push(scripts);
+    }
 
-    #[test]
-    fn test_read_package_json() {
-        let json = r#"{
-            "scripts": {
-                "build": "cargo build",
-                "test": "cargo test"
-            }
-        }"#;
+    let mut out = Vec::new();
+    changes::print_set_diff(&mut out, &cli.path, &changes::compute_set_diff(&sets))?;
+    write_output(cli, &out)
+}
 
-        let package: PackageJson = serde_json::from_str(json).unwrap();
-        assert_eq!(package.scripts.len(), 2);
+/// Whether to switch to `--workspaces` without being asked: for a table
+/// listing in a monorepo root that uses nothing the workspace listing would
+/// leave out (see `AUTO_WORKSPACES_ARGS`), unless `--no-auto-workspaces`
+fn auto_workspaces(cli: &Cli, package_path: &Path) -> Result<bool> {
+    let plain_listing = matches!(cli.format, OutputFormat::Table)
+        && cli
+            .given
+            .iter()
+            .all(|id| AUTO_WORKSPACES_ARGS.contains(&id.as_str()));
+    if cli.no_auto_workspaces || !plain_listing {
+        return Ok(false);
+    }
+    let root = read_package_json(package_path)?;
+    let declared =
+        workspaces::declared_patterns(&package_dir(package_path), root.workspaces.as_ref())?;
+    Ok(declared.is_some())
+}
+
+/// `--workspaces`: a section for each member package, in path order. When
+/// `auto`, a line up top says where the workspaces were found.
+fn list_workspaces(cli: &Cli, package_path: &Path, auto: bool) -> Result<Vec<String>> {
+    let root = read_package_json(package_path)?;
+    let root_dir = package_dir(package_path);
+    let declared = workspaces::declared_patterns(&root_dir, root.workspaces.as_ref())?;
+    let Some((patterns, source)) = declared else {
+        bail!("No workspaces defined in {}", package_path.display());
+    };
+    if !matches!(cli.format, OutputFormat::Table) {
+        bail!("--workspaces only supports the table format");
+    }
+
+    let manifests = workspaces::member_manifests(&root_dir, &patterns)?;
+    let mut warnings = Vec::new();
+    let mut members = Vec::new();
+    let mut missing = Vec::new();
+    for manifest in manifests {
+        let package = read_package_json(&manifest)?;
+        if cli.skip_private && package.private {
+            continue;
+        }
+        let name = package.name.clone().unwrap_or_else(|| {
+            let dir = package_dir(&manifest);
+            dir.file_name()
+                .unwrap_or_default()
+                .to_string_lossy()
+                .to_string()
+        });
+
+        for warning in collect_warnings(cli, &manifest, &package)? {
+            let warning = format!("{}: {}", name, warning);
+            print_warning(cli, &warning);
+            warnings.push(warning);
+        }
+
+        let mut scripts: Vec<_> = package.scripts.into_iter().collect();
+        let absent = checks::missing_scripts(&scripts, &cli.require);
+        if !absent.is_empty() {
+            missing.push(format!("{} ({})", name, absent.join(", ")));
+        }
+        apply_filters(cli, &mut scripts);
+        sort::sort_scripts(&mut scripts, sort_by(cli), &sort_order(cli, &manifest)?);
+        members.push((name, scripts));
+    }
+    if !missing.is_empty() {
+        bail!("Missing required script(s) in {}", missing.join(", "));
+    }
+
+    let mut out = Vec::new();
+    if auto {
+        let note = format!(
+            "Workspaces from {} (--no-auto-workspaces for the root's own scripts)",
+            source
+        );
+        writeln!(out)?;
+        writeln!(out, "   {}", note.dimmed())?;
+    }
+    print_workspaces(&mut out, &members, cli)?;
+    write_output(cli, &out)?;
+    Ok(warnings)
+}
+
+/// package.json with the `--merge` overlay applied and the descriptions
+/// `--script-info-key` points at, along with the commands the overlay
+/// replaced
+fn read_listed_package(
+    cli: &Cli,
+    package_path: &Path,
+) -> Result<(PackageJson, Option<HashMap<String, String>>)> {
+    let mut package = read_package_json(package_path)?;
+    let overridden = match &cli.merge {
+        Some(overlay) => Some(merge_scripts(&mut package, overlay)?),
+        None => None,
+    };
+    let info_key = match &cli.script_info_key {
+        Some(key) => Some(key.clone()),
+        None => config::Config::load(&package_dir(package_path))?.script_info_key,
+    };
+    if let Some(key) = info_key {
+        use_script_info_key(&mut package, &key)?;
+    }
+    Ok((package, overridden))
+}
+
+/// `scripts` the way they're listed: filtered by --filter, --since and the
+/// like, then sorted. Also returns how many --hide-long-commands left out.
+fn listed_scripts(
+    cli: &Cli,
+    package_path: &Path,
+    mut scripts: Vec<(String, String)>,
+) -> Result<(Vec<(String, String)>, usize)> {
+    apply_filters(cli, &mut scripts);
+    if let Some(since) = cli.since {
+        let entries = match history::history_file() {
+            Some(history_file) => history::read(&history_file)?,
+            None => Vec::new(),
+        };
+        let package = history::package_key(package_path);
+        let recent = history::scripts_run_since(&entries, &package, since);
+        scripts.retain(|(name, _)| recent.contains(name));
+    }
+    let hidden = match cli.hide_long_commands {
+        Some(max) => hide_long_commands(&mut scripts, max),
+        None => 0,
+    };
+
+    sort::sort_scripts(&mut scripts, sort_by(cli), &sort_order(cli, package_path)?);
+    Ok((scripts, hidden))
+}
+
+/// The script `--run` names: the one called `name`, or failing that, with a
+/// number, the script listed at that place (as `--numbered` shows them)
+fn run_target(cli: &Cli, package_path: &Path, package: &PackageJson, name: &str) -> Result<String> {
+    if package.scripts.contains_key(name) {
+        return Ok(name.to_string());
+    }
+    let Ok(number) = name.parse::<usize>() else {
+        return Ok(name.to_string());
+    };
+    let all_scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+    let (scripts, _) = listed_scripts(cli, package_path, all_scripts)?;
+    match number.checked_sub(1).and_then(|index| scripts.get(index)) {
+        Some((name, _)) => Ok(name.clone()),
+        None => bail!(
+            "No script number {} in {}: {} are listed",
+            number,
+            package_path.display(),
+            scripts.len()
+        ),
+    }
+}
+
+/// Compare the package's scripts against the last `--highlight-changed`
+/// listing and remember them for the next one. `None` the first time round.
+fn highlight_changes(package_path: &Path, package: &PackageJson) -> Result<Option<ScriptDiff>> {
+    let Some(snapshot_file) = changes::snapshot_file() else {
+        return Ok(None);
+    };
+    let key = history::package_key(package_path);
+    let scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+
+    let previous = changes::previous(&snapshot_file, &key)?;
+    changes::save(&snapshot_file, &key, &scripts)?;
+    Ok(previous.map(|previous| changes::diff_with_previous(&scripts, &previous)))
+}
+
+/// `--merge`: lay the scripts of the package.json at `overlay` over
+/// `package`'s, returning the original command of each one it changed
+fn merge_scripts(package: &mut PackageJson, overlay: &Path) -> Result<HashMap<String, String>> {
+    let overlay = read_package_json(overlay)?;
+    let mut overridden = HashMap::new();
+    for (name, command) in overlay.scripts {
+        match package.scripts.insert(name.clone(), command.clone()) {
+            Some(original) if original != command => {
+                overridden.insert(name, original);
+            }
+            _ => {}
+        }
+    }
+    Ok(overridden)
+}
+
+/// Take the descriptions from `key` instead of `scripts-info`; there are none
+/// if package.json doesn't have it
+fn use_script_info_key(package: &mut PackageJson, key: &str) -> Result<()> {
+    if key == "scripts-info" {
+        return Ok(());
+    }
+    package.scripts_info = match package.other.remove(key) {
+        Some(value) => serde_json::from_value(value)
+            .with_context(|| format!("\"{}\" in package.json isn't a map of descriptions", key))?,
+        None => HashMap::new(),
+    };
+    Ok(())
+}
+
+/// `--summarize`: what each script does, for those matching a rule from
+/// .scriptrc.json or the built-in ones
+fn summarize(package_path: &Path, scripts: &[(String, String)]) -> Result<HashMap<String, String>> {
+    let mut rules = config::Config::load(&package_dir(package_path))?.summaries;
+    rules.extend(summary::built_in_rules());
+    Ok(scripts
+        .iter()
+        .filter_map(|(name, command)| {
+            summary::summarize_command(command, &rules).map(|summary| (name.clone(), summary))
+        })
+        .collect())
+}
+
+fn get_script<'a>(scripts: &'a [(String, String)], name: &str) -> Option<&'a str> {
+    scripts
+        .iter()
+        .find(|(n, _)| n == name)
+        .map(|(_, command)| command.as_str())
+}
+
+fn apply_filters(cli: &Cli, scripts: &mut Vec<(String, String)>) {
+    // Filter if specified
+    if let Some(pattern) = &cli.filter {
+        let pattern = pattern.to_lowercase();
+        scripts.retain(|(name, _)| name.to_lowercase().contains(&pattern));
+    }
+
+    if let Some(pattern) = &cli.filter_by_command {
+        filter_by_command(scripts, pattern);
+    }
+
+    if let Some(pattern) = &cli.search {
+        let search = search_regex(pattern);
+        scripts.retain(|(name, command)| search.is_match(name) || search.is_match(command));
+    }
+
+    omit_prefixed(scripts, &cli.omit_prefixed);
+}
+
+// `--search`: the pattern taken literally, ignoring case
+fn search_regex(pattern: &str) -> regex::Regex {
+    regex::RegexBuilder::new(&regex::escape(pattern))
+        .case_insensitive(true)
+        .build()
+        .expect("an escaped pattern is a valid regex")
+}
+
+/// `text` with the `--search` matches in bold green and the rest styled by
+/// `base`; `None` when there's nothing to highlight or colors are off
+fn highlight_search(text: &str, cli: &Cli, base: impl Fn(&str) -> String) -> Option<String> {
+    let pattern = cli.search.as_deref()?;
+    if !control::SHOULD_COLORIZE.should_colorize() {
+        return None;
+    }
+    mark_matches(text, &search_regex(pattern), base)
+}
+
+fn mark_matches(
+    text: &str,
+    search: &regex::Regex,
+    base: impl Fn(&str) -> String,
+) -> Option<String> {
+    let mut marked = String::new();
+    let mut end = 0;
+    // Empty stretches are left out rather than styled, escape codes and all
+    let push_base = |marked: &mut String, piece: &str| {
+        if !piece.is_empty() {
+            marked.push_str(&base(piece));
+        }
+    };
+    for found in search.find_iter(text) {
+        push_base(&mut marked, &text[end..found.start()]);
+        marked.push_str(&format!("\x1b[1;32m{}\x1b[0m", found.as_str()));
+        end = found.end();
+    }
+    if end == 0 {
+        return None;
+    }
+    push_base(&mut marked, &text[end..]);
+    Some(marked)
+}
+
+/// Keep the scripts whose command contains `pattern`, ignoring case
+fn filter_by_command(scripts: &mut Vec<(String, String)>, pattern: &str) {
+    let pattern = pattern.to_lowercase();
+    scripts.retain(|(_, command)| command.to_lowercase().contains(&pattern));
+}
+
+/// Drop scripts starting with any of `prefixes`, e.g. lifecycle hooks like
+/// `prepare` or `postinstall` that clutter aggregated workspace listings
+fn omit_prefixed(scripts: &mut Vec<(String, String)>, prefixes: &[String]) {
+    scripts.retain(|(name, _)| {
+        !prefixes
+            .iter()
+            .any(|prefix| name.starts_with(prefix.as_str()))
+    });
+}
+
+/// Drop scripts whose command is longer than `max` characters, returning how
+/// many were dropped
+fn hide_long_commands(scripts: &mut Vec<(String, String)>, max: usize) -> usize {
+    let before = scripts.len();
+    scripts.retain(|(_, command)| command.chars().count() <= max);
+    before - scripts.len()
+}
+
+// `--no-sort` is shorthand for `--sort-by none`
+fn sort_by(cli: &Cli) -> SortBy {
+    if cli.no_sort {
+        SortBy::None
+    } else {
+        cli.sort_by
+    }
+}
+
+/// The names `sort::sort_scripts` orders by position: as written in
+/// package.json for `--sort-by source`, .scriptrc.json's `order` for
+/// `--sort-by priority`
+fn sort_order(cli: &Cli, package_path: &Path) -> Result<Vec<String>> {
+    Ok(match sort_by(cli) {
+        SortBy::Source => sort::read_source_order(package_path)?,
+        SortBy::Priority => config::Config::load(&package_dir(package_path))?.order,
+        _ => Vec::new(),
+    })
+}
+
+/// Whether to force colors on or off, or `None` to leave it to `colored`'s
+/// own detection. Escape codes are kept out of regular `--output` files
+/// even with `--color always`, unless `--force-color-file` says otherwise.
+fn color_override(cli: &Cli) -> Option<bool> {
+    let to_file = cli.output.as_deref().is_some_and(is_regular_file_target);
+    match cli.color {
+        _ if cli.plain => Some(false),
+        ColorChoice::Never => Some(false),
+        _ if to_file && !cli.force_color_file => Some(false),
+        ColorChoice::Always => Some(true),
+        ColorChoice::Auto => None,
+    }
+}
+
+// A path that doesn't exist yet will be created as a regular file; devices
+// and pipes such as /dev/tty or /dev/stdout aren't files
+fn is_regular_file_target(path: &Path) -> bool {
+    fs::metadata(path).map_or(true, |m| m.is_file())
+}
+
+/// Write rendered output to `--output`, or to stdout by default
+fn write_output(cli: &Cli, buf: &[u8]) -> Result<()> {
+    let mut converted = None;
+    if cli.ascii_only {
+        converted = Some(ascii::transliterate(&String::from_utf8_lossy(buf)).into_bytes());
+    }
+    if cli.output_encoding != OutputEncoding::Utf8 {
+        let text = String::from_utf8_lossy(converted.as_deref().unwrap_or(buf)).into_owned();
+        let (encoded, replaced) = encoding::encode(&text, cli.output_encoding);
+        if replaced > 0 {
+            let warning = format!("{} character(s) not in Latin-1 were written as ?", replaced);
+            print_warning(cli, &warning);
+        }
+        converted = Some(encoded);
+    }
+    let buf = converted.as_deref().unwrap_or(buf);
+    match &cli.output {
+        Some(path) => {
+            fs::write(path, buf).with_context(|| format!("Failed to write {}", path.display()))
+        }
+        None if cli.pager => pager::page(buf),
+        None => match &cli.tee {
+            Some(path) => tee_stdout(buf, path),
+            None => write_stdout(buf),
+        },
+    }
+}
+
+fn print_warning(cli: &Cli, warning: &str) {
+    let mut line = format!("⚠️  {}", warning);
+    if cli.ascii_only {
+        line = ascii::transliterate(&line);
+    }
+    eprintln!("{}", line.yellow());
+}
+
+/// Write rendered output to stdout. If the reader went away (`sl | head`)
+/// there's nobody left to tell, so exit quietly instead of erroring.
+fn write_stdout(buf: &[u8]) -> Result<()> {
+    let mut stdout = io::stdout().lock();
+    match stdout.write_all(buf).and_then(|_| stdout.flush()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => std::process::exit(0),
+        result => Ok(result.context("Failed to write to stdout")?),
+    }
+}
+
+/// `--tee`: write rendered output to `path` and to stdout
+fn tee_stdout(buf: &[u8], path: &Path) -> Result<()> {
+    let file =
+        fs::File::create(path).with_context(|| format!("Failed to create {}", path.display()))?;
+    let mut tee = tee::TeeWriter::new(file, io::stdout().lock());
+    match tee.write_all(buf).and_then(|_| tee.flush()) {
+        Err(e) if e.kind() == io::ErrorKind::BrokenPipe => std::process::exit(0),
+        result => {
+            let context = || format!("Failed to write to {} and stdout", path.display());
+            Ok(result.with_context(context)?)
+        }
+    }
+}
+
+/// With `--strict`, any warning turns into a failing exit code
+fn exit_for_warnings(cli: &Cli, warnings: &[String]) -> Result<()> {
+    if cli.strict && !warnings.is_empty() {
+        std::process::exit(1);
+    }
+    Ok(())
+}
+
+/// Run the opt-in checks, returning a warning for each problem found
+fn collect_warnings(cli: &Cli, package_path: &Path, package: &PackageJson) -> Result<Vec<String>> {
+    let package_dir = package_dir(package_path);
+    let mut warnings = Vec::new();
+
+    if cli.from_package_lock {
+        let node_range = package.engines.get("node").map(String::as_str);
+        if let Some(requirement) = engines::node_requirement(&package_dir, node_range)? {
+            warnings.extend(engines::check_node(&requirement));
+        }
+    }
+
+    if cli.check_lock {
+        if let Some(lockfile) = package_manager::detect_lockfile(&package_dir) {
+            if package_manager::is_stale(package_path, &lockfile)? {
+                warnings.push(format!(
+                    "package.json is newer than {}, run `{} install`",
+                    lockfile.file_name(),
+                    lockfile.manager.name()
+                ));
+            }
+        }
+    }
+
+    if cli.version_bump_check {
+        if let Some(version) = &package.version {
+            warnings.extend(git::check_version_tag(&package_dir, version));
+        }
+    }
+
+    let mut scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+    scripts.sort();
+
+    if cli.check_shebang {
+        warnings.extend(checks::check_shebangs(&scripts, &package_dir));
+    }
+
+    if cli.check_portability {
+        warnings.extend(checks::check_portability(&scripts));
+    }
+
+    if cli.validate || cli.validate_names {
+        warnings.extend(checks::check_names(&scripts));
+    }
+
+    if cli.validate {
+        warnings.extend(checks::check_runners(&scripts, &package_dir));
+    }
+
+    Ok(warnings)
+}
+
+fn read_package_json(path: &Path) -> Result<PackageJson> {
+    let content = match fs::read_to_string(path) {
+        Ok(c) => c,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => {
+            // Get current directory name
+            let current_dir = env::current_dir().ok();
+            let dir_name = current_dir
+                .as_ref()
+                .and_then(|p| p.file_name())
+                .and_then(|n| n.to_str())
+                .unwrap_or("unknown");
+            
+            eprintln!();
+            eprintln!("   {}", dir_name.red().bold());
+            eprintln!();
+            eprintln!("   No package.json file found:");
+            eprintln!("     {}", current_dir.as_ref().map(|p| p.display().to_string()).unwrap_or_default());
+            eprintln!();
+            
+            std::process::exit(1);
+        }
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display()))?,
+    };
+
+    let package: PackageJson = serde_json::from_str(&content)
+        .with_context(|| format!("Failed to parse {} as JSON", path.display()))?;
+
+    Ok(package)
+}
+
+/// Directory containing package.json, for finding sibling files
+fn package_dir(package_path: &Path) -> PathBuf {
+    match package_path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir.to_path_buf(),
+        _ => PathBuf::from("."),
+    }
+}
+
+/// Script name as shown next to its command, shortened per `--truncate-names`
+fn display_name(name: &str, cli: &Cli) -> String {
+    let name = as_displayed(name, cli);
+    match cli.truncate_names {
+        Some(max) if !cli.no_truncate => truncate(&name, max, &cli.truncate_marker),
+        _ => name,
+    }
+}
+
+/// Command as shown after a `prefix_width`-column prefix, shortened so the
+/// line fits in `--max-width`
+fn display_command(command: &str, prefix_width: usize, cli: &Cli) -> String {
+    let mut command = as_displayed(command, cli);
+    if cli.strip_run_prefix && !control::SHOULD_COLORIZE.should_colorize() {
+        command = shell::split_run_prefix(&command).1.to_string();
+    }
+    match cli.max_width {
+        Some(max) if max > 0 && !cli.no_truncate => {
+            let max = max.saturating_sub(prefix_width);
+            truncate(&command, max, &cli.truncate_marker)
+        }
+        _ => command,
+    }
+}
+
+// With --ascii-only, names and commands are transliterated up front (not
+// just on the way out) so that widths are measured on what's shown
+fn as_displayed(text: &str, cli: &Cli) -> String {
+    if cli.ascii_only {
+        ascii::transliterate(text)
+    } else {
+        text.to_string()
+    }
+}
+
+/// `command` syntax-highlighted with `--color-command` and its run prefix
+/// dimmed with `--strip-run-prefix`, unless colors are off
+fn styled_command(command: &str, cli: &Cli) -> String {
+    if !control::SHOULD_COLORIZE.should_colorize() {
+        return command.to_string();
+    }
+    let (prefix, rest) = if cli.strip_run_prefix {
+        shell::split_run_prefix(command)
+    } else {
+        ("", command)
+    };
+    let rest = if cli.color_command {
+        shell::highlight_shell(rest)
+    } else {
+        rest.to_string()
+    };
+    if prefix.is_empty() {
+        rest
+    } else {
+        format!("{}{}", prefix.dimmed(), rest)
+    }
+}
+
+/// Shorten `text` to at most `max` characters, marking the cut with `marker`
+/// (itself cut short if even it doesn't fit)
+fn truncate(text: &str, max: usize, marker: &str) -> String {
+    if text.chars().count() <= max {
+        return text.to_string();
+    }
+    let keep = max.saturating_sub(marker.chars().count());
+    let mut truncated: String = text.chars().take(keep).collect();
+    truncated.extend(marker.chars().take(max));
+    truncated
+}
+
+// Shown after commands that need arguments with --mark-runnable
+const ARGS_MARKER: &str = "  [args]";
+
+fn print_scripts(
+    out: &mut impl Write,
+    _package: &PackageJson,
+    scripts: &[(String, String)],
+    hidden: usize,
+    annotations: &Annotations,
+    cli: &Cli,
+) -> io::Result<()> {
+    if cli.names_only {
+        for (name, _) in scripts {
+            writeln!(out, "{}", name)?;
+        }
+        return Ok(());
+    }
+    if cli.commands_only {
+        for (_, command) in scripts {
+            writeln!(out, "{}", command)?;
+        }
+        return Ok(());
+    }
+
+    // Use current directory name (like rousan/sl does)
+    let module_name = env::current_dir()
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or_else(|| "unknown".to_string());
+    
+    print_section(out, &module_name, scripts, annotations, cli)?;
+
+    let footer = format!("   Found {} script(s)", format_count(scripts.len(), cli));
+    writeln!(out, "{}", footer.dimmed())?;
+    if hidden > 0 {
+        let footer = format!(
+            "   {} script(s) hidden with commands over {} characters",
+            format_count(hidden, cli),
+            cli.hide_long_commands.unwrap_or_default()
+        );
+        writeln!(out, "{}", footer.dimmed())?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// `n` with the system locale's thousands separators ("1,234"), or as bare
+/// digits with `--plain` or when the locale can't be read
+fn format_count(n: usize, cli: &Cli) -> String {
+    match SystemLocale::default() {
+        Ok(locale) if !cli.plain => n.to_formatted_string(&locale),
+        _ => n.to_string(),
+    }
+}
+
+/// Per-script extras shown in the table
+#[derive(Default)]
+struct Annotations<'a> {
+    /// `--highlight-changed`
+    changes: Option<&'a ScriptDiff>,
+    /// `--summarize`, by script name
+    summaries: Option<&'a HashMap<String, String>>,
+    /// `--print-pm-command`, by script name
+    invocations: Option<&'a HashMap<String, String>>,
+    /// `--merge`: the original command of each overridden script
+    overridden: Option<&'a HashMap<String, String>>,
+}
+
+/// A package's name and its scripts, aligned on the name column
+fn print_section(
+    out: &mut impl Write,
+    title: &str,
+    scripts: &[(String, String)],
+    annotations: &Annotations,
+    cli: &Cli,
+) -> io::Result<()> {
+    print_header(out, title)?;
+
+    let names: Vec<_> = scripts
+        .iter()
+        .map(|(name, _)| display_name(name, cli))
+        .collect();
+
+    // Calculate max script name length for padding
+    let max_len = names
+        .iter()
+        .map(|name| name.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    let markers: Vec<_> = scripts
+        .iter()
+        .map(|(_, command)| {
+            if cli.mark_runnable && shell::expects_args(command) {
+                ARGS_MARKER
+            } else {
+                ""
+            }
+        })
+        .collect();
+    // With --show-command-hash, a column of hashes ahead of the commands
+    let hash_width = if cli.show_command_hash {
+        changes::HASH_LEN + 2
+    } else {
+        0
+    };
+    let commands: Vec<_> = scripts
+        .iter()
+        .zip(&markers)
+        .map(|((_, command), marker)| {
+            let prefix_width = "    - ".len()
+                + max_len
+                + " : ".len()
+                + hash_width
+                + marker.len()
+                + table::extra_width(cli.table_style);
+            display_command(command, prefix_width, cli)
+        })
+        .collect();
+    // With --summarize, the summaries line up in a column after the commands
+    let command_width = commands
+        .iter()
+        .zip(&markers)
+        .map(|(command, marker)| command.chars().count() + marker.len())
+        .max()
+        .unwrap_or(0);
+
+    let invocation_width = scripts
+        .iter()
+        .filter_map(|(name, _)| annotations.invocations?.get(name))
+        .map(|invocation| invocation.chars().count())
+        .max()
+        .unwrap_or(0);
+
+    // Each script as " - " prefix, name and command, with its width on screen
+    let mut rows = Vec::new();
+    for (i, (name, (full_name, raw_command))) in names.iter().zip(scripts).enumerate() {
+        if cli.names_only {
+            rows.push((format!("- {}", name), "- ".len() + name.chars().count()));
+            continue;
+        }
+        // The bullet doubles as the --highlight-changed sigil
+        let sigil = annotations
+            .changes
+            .and_then(|changes| changes.sigil(full_name));
+        let bullet = match sigil {
+            Some('+') => "+".green(),
+            Some(sigil) => sigil.to_string().yellow(),
+            None => "-".normal(),
+        };
+        let padded_name = format!("{:<width$}", name, width = max_len);
+        let (command, marker) = (&commands[i], markers[i]);
+        let hash = if cli.show_command_hash {
+            format!("{}  ", changes::command_hash(raw_command).dimmed())
+        } else {
+            String::new()
+        };
+        let gray = |text: &str| text.truecolor(128, 128, 128).to_string();
+        let padded_name =
+            highlight_search(&padded_name, cli, gray).unwrap_or_else(|| gray(&padded_name));
+        let styled = highlight_search(command, cli, str::to_string)
+            .unwrap_or_else(|| styled_command(command, cli));
+        let mut row = format!(
+            "{} {} : {}{}{}",
+            bullet,
+            padded_name,
+            hash,
+            styled,
+            marker.dimmed()
+        );
+        let command_at = "- ".len() + max_len + " : ".len() + hash_width;
+        let mut width = command_at + command.chars().count() + marker.len();
+        // The invocations, then the summaries, each lined up in a column
+        let mut column_at = command_at + command_width;
+        if let Some(invocations) = annotations.invocations {
+            if let Some(invocation) = invocations.get(full_name) {
+                let padding = column_at - width;
+                row.push_str(&format!("{:padding$}  {}", "", invocation.dimmed()));
+                width = column_at + 2 + invocation.chars().count();
+            }
+            column_at += 2 + invocation_width;
+        }
+        if let Some(summary) = annotations.summaries.and_then(|s| s.get(full_name)) {
+            let padding = column_at - width;
+            row.push_str(&format!("{:padding$}  {}", "", summary.cyan()));
+            width = column_at + 2 + summary.chars().count();
+        }
+        rows.push((row, width));
+
+        // The command it replaced, under the one that replaced it
+        if let Some(original) = annotations.overridden.and_then(|o| o.get(full_name)) {
+            let indent = "- ".len() + max_len + " : ".len() + hash_width;
+            let was = format!("(was: {})", original);
+            let width = indent + was.chars().count();
+            rows.push((format!("{:indent$}{}", "", was.dimmed()), width));
+        }
+    }
+    table::write_rows(out, &rows, cli.table_style)?;
+
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Scripts gone since the last `--highlight-changed` listing
+fn print_removed(out: &mut impl Write, removed: &[(String, String)]) -> io::Result<()> {
+    if removed.is_empty() {
+        return Ok(());
+    }
+    writeln!(out, "   {}", "Removed".red())?;
+    writeln!(out)?;
+    for (name, command) in removed {
+        let line = format!("    - {} : {}", name, command);
+        writeln!(out, "{}", line.red())?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+// Print module name (3 spaces prefix, green)
+fn print_header(out: &mut impl Write, title: &str) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "   {}", title.green())?;
+    writeln!(out)
+}
+
+/// One section per workspace package; packages without scripts are left out,
+/// listed as such, or gathered at the end depending on `--empty`
+fn print_workspaces(
+    out: &mut impl Write,
+    members: &[(String, Vec<(String, String)>)],
+    cli: &Cli,
+) -> io::Result<()> {
+    let mut empty = Vec::new();
+    for (name, scripts) in members {
+        if !scripts.is_empty() {
+            print_section(out, name, scripts, &Annotations::default(), cli)?;
+            continue;
+        }
+        match cli.empty {
+            Empty::Skip => {}
+            Empty::Show => {
+                print_header(out, name)?;
+                writeln!(out, "    {}", "(no scripts)".dimmed())?;
+                writeln!(out)?;
+            }
+            Empty::Warn => empty.push(name),
+        }
+    }
+
+    if !empty.is_empty() {
+        writeln!(out)?;
+        writeln!(out, "   {}", "⚠️  No scripts".yellow())?;
+        writeln!(out)?;
+        for name in empty {
+            writeln!(out, "    - {}", name.dimmed())?;
+        }
+        writeln!(out)?;
+    }
+    Ok(())
+}
+
+/// The "Available binaries" list, noting which ones scripts already run
+fn print_binaries(
+    out: &mut impl Write,
+    bins: &[String],
+    scripts: &[(String, String)],
+) -> io::Result<()> {
+    if bins.is_empty() {
+        return Ok(());
+    }
+    let used = bins::used_in_scripts(bins, scripts);
+
+    writeln!(out, "   {}", "Available binaries".green())?;
+    writeln!(out)?;
+    for bin in bins {
+        if used.contains(bin.as_str()) {
+            writeln!(out, "    - {} {}", bin, "(used in scripts)".dimmed())?;
+        } else {
+            writeln!(out, "    - {}", bin)?;
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+fn print_list(out: &mut impl Write, scripts: &[(String, String)], cli: &Cli) -> io::Result<()> {
+    // `--numbered`: right-aligned so the names still line up past 9
+    let number_width = scripts.len().to_string().len();
+    writeln!(out)?;
+    for (index, (name, command)) in scripts.iter().enumerate() {
+        let prefix = if cli.numbered {
+            format!("   {:>number_width$}. ", index + 1)
+        } else {
+            "   ".to_string()
+        };
+        if cli.names_only {
+            writeln!(out, "{}{}", prefix, name)?;
+        } else if cli.commands_only {
+            writeln!(out, "{}{}", prefix, command)?;
+        } else {
+            let name = display_name(name, cli);
+            let command = display_command(command, prefix.len() + name.chars().count() + 2, cli);
+            let name = highlight_search(&name, cli, str::to_string).unwrap_or(name);
+            let command = highlight_search(&command, cli, str::to_string)
+                .unwrap_or_else(|| styled_command(&command, cli));
+            writeln!(out, "{}{}: {}", prefix, name, command)?;
+        }
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+// Serializes as a name → command map, in `scripts` order
+struct ScriptMap<'a>(&'a [(String, String)]);
+
+impl serde::Serialize for ScriptMap<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_map(self.0.iter().map(|(name, command)| (name, command)))
+    }
+}
+
+/// The scripts as an indented JSON object, serialized straight into `out`
+/// so that thousands of them don't need a copy as a map and a string first
+fn print_json(out: &mut impl Write, scripts: &[(String, String)]) -> Result<()> {
+    write!(out, "\n   ")?;
+    let indented = indent::IndentWriter::new(&mut *out, "   ");
+    serde_json::to_writer_pretty(indented, &ScriptMap(scripts))?;
+    write!(out, "\n\n")?;
+    Ok(())
+}
+
+/// JSON array with one object per script, holding just the requested fields
+fn json_fields(
+    package_path: &Path,
+    package: &PackageJson,
+    scripts: &[(String, String)],
+    fields: &[Field],
+) -> Result<serde_json::Value> {
+    let names: Vec<&str> = package.scripts.keys().map(String::as_str).collect();
+    let path = if fields.contains(&Field::Path) {
+        let path = std::path::absolute(package_path)
+            .with_context(|| format!("Failed to resolve {}", package_path.display()))?;
+        let path = path.to_string_lossy();
+        Some(if cfg!(windows) {
+            path.replace('\\', "/")
+        } else {
+            path.into_owned()
+        })
+    } else {
+        None
+    };
+    let manager = package_manager::PackageManager::detect(&package_dir(package_path));
+    let objects = scripts
+        .iter()
+        .map(|(name, command)| {
+            let mut object = serde_json::Map::new();
+            for field in fields {
+                let (key, value) = match field {
+                    Field::Name => ("name", name.as_str().into()),
+                    Field::Command => ("command", command.as_str().into()),
+                    Field::Description => {
+                        let description = package.scripts_info.get(name).map(String::as_str);
+                        ("description", description.into())
+                    }
+                    Field::References => {
+                        let references = graph::script_references(command, &names);
+                        ("references", references.into())
+                    }
+                    Field::Path => ("path", path.as_deref().into()),
+                    Field::PackageManager => ("packageManager", manager.name().into()),
+                };
+                object.insert(key.to_string(), value);
+            }
+            serde_json::Value::Object(object)
+        })
+        .collect();
+    Ok(serde_json::Value::Array(objects))
+}
+
+fn print_json_fields(
+    out: &mut impl Write,
+    package_path: &Path,
+    package: &PackageJson,
+    scripts: &[(String, String)],
+    fields: &[Field],
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(&json_fields(package_path, package, scripts, fields)?)?;
+    writeln!(out)?;
+    for line in json.lines() {
+        writeln!(out, "   {}", line)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_read_package_json() {
+        let json = r#"{
+            "scripts": {
+                "build": "cargo build",
+                "test": "cargo test"
+            }
+        }"#;
+
+        let package: PackageJson = serde_json::from_str(json).unwrap();
+        assert_eq!(package.scripts.len(), 2);
+    }
+
+    #[test]
+    fn test_no_sort_keeps_file_order() {
+        let package: PackageJson = serde_json::from_str(
+            r#"{ "scripts": { "start": "node .", "build": "tsc", "test": "jest", "lint": "eslint ." } }"#,
+        )
+        .unwrap();
+        let sorted = |args: &[&str]| {
+            let cli = Cli::parse_from(["sl"].iter().chain(args));
+            let mut scripts: Vec<_> = package.scripts.clone().into_iter().collect();
+            sort::sort_scripts(&mut scripts, sort_by(&cli), &[]);
+            scripts
+                .into_iter()
+                .map(|(name, _)| name)
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(sorted(&[]), ["build", "lint", "start", "test"]);
+        assert_eq!(sorted(&["--no-sort"]), ["start", "build", "test", "lint"]);
+        assert_eq!(sorted(&["--sort-by", "none"]), sorted(&["--no-sort"]));
+        let error = Cli::try_parse_from(["sl", "--no-sort", "--sort-by", "name"]).err();
+        let kind = error.map(|error| error.kind());
+        assert_eq!(kind, Some(clap::error::ErrorKind::ArgumentConflict));
+    }
+
+    #[test]
+    fn test_print_list_writes_to_buffer() {
+        let scripts = vec![
+            ("build".to_string(), "cargo build".to_string()),
+            ("test".to_string(), "cargo test".to_string()),
+        ];
+
+        let mut out = Vec::new();
+        print_list(&mut out, &scripts, &Cli::parse_from(["sl"])).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   build: cargo build\n   test: cargo test\n\n"
+        );
+    }
+
+    #[test]
+    fn test_strip_run_prefix() {
+        control::set_override(false);
+        let scripts = vec![
+            ("ci".to_string(), "npm run lint && npm test".to_string()),
+            ("dev".to_string(), "pnpm run start --watch".to_string()),
+        ];
+
+        let mut out = Vec::new();
+        let cli = Cli::parse_from(["sl", "-F", "list", "--strip-run-prefix"]);
+        print_list(&mut out, &scripts, &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   ci: lint && npm test\n   dev: start --watch\n\n"
+        );
+    }
+
+    #[test]
+    fn test_numbered_list() {
+        control::set_override(false);
+        let scripts: Vec<_> = (1..=10)
+            .map(|n| (format!("s{}", n), "tsc".to_string()))
+            .collect();
+
+        let mut out = Vec::new();
+        let cli = Cli::parse_from(["sl", "-F", "list", "--numbered"]);
+        print_list(&mut out, &scripts[8..], &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   1. s9: tsc\n   2. s10: tsc\n\n"
+        );
+
+        let mut out = Vec::new();
+        print_list(&mut out, &scripts, &cli).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.starts_with("\n    1. s1: tsc\n    2. s2: tsc\n"));
+        assert!(out.ends_with("   10. s10: tsc\n\n"));
+    }
+
+    #[test]
+    fn test_run_target() {
+        let dir = tempfile::tempdir().unwrap();
+        let package_path = dir.path().join("package.json");
+        let json = r#"{ "scripts": { "lint": "eslint .", "build": "tsc", "2": "echo two", "test": "jest" } }"#;
+        fs::write(&package_path, json).unwrap();
+        let package = read_package_json(&package_path).unwrap();
+        let target = |args: &[&str], name| {
+            let cli = Cli::parse_from([&["sl"], args].concat());
+            run_target(&cli, &package_path, &package, name).ok()
+        };
+
+        // Numbers count in the listing's order: 2, build, lint, test
+        assert_eq!(target(&[], "3").as_deref(), Some("lint"));
+        assert_eq!(target(&["--filter", "te"], "1").as_deref(), Some("test"));
+        // A script called that wins over the number
+        assert_eq!(target(&[], "2").as_deref(), Some("2"));
+        assert_eq!(target(&[], "build").as_deref(), Some("build"));
+        assert_eq!(target(&[], "0"), None);
+        assert_eq!(target(&[], "5"), None);
+    }
+
+    #[test]
+    fn test_omit_prefixed() {
+        let mut scripts: Vec<_> = ["build", "postinstall", "prepare", "prepublishOnly", "test"]
+            .iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect();
+
+        omit_prefixed(&mut scripts, &["prepare".to_string(), "post".to_string()]);
+
+        let names: Vec<_> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["build", "prepublishOnly", "test"]);
+    }
+
+    #[test]
+    fn test_truncate() {
+        assert_eq!(truncate("build", 10, "..."), "build");
+        assert_eq!(truncate("build:production", 10, "..."), "build:p...");
+        assert_eq!(truncate("démarrer:prod", 8, "..."), "démar...");
+        assert_eq!(truncate("build", 2, "..."), "..");
+        assert_eq!(truncate("build:production", 10, "…"), "build:pro…");
+        assert_eq!(truncate("démarrer:prod", 8, " →"), "démarr →");
+        assert_eq!(truncate("build", 0, "…"), "");
+    }
+
+    #[test]
+    fn test_truncate_names_keeps_commands_aligned() {
+        control::set_override(false);
+        let scripts = vec![
+            ("build:production:all".to_string(), "webpack".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let cli = Cli::parse_from(["sl", "--truncate-names", "8"]);
+
+        let mut out = Vec::new();
+        print_scripts(
+            &mut out,
+            &PackageJson::default(),
+            &scripts,
+            0,
+            &Annotations::default(),
+            &cli,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("    - build:p… : webpack\n"));
+        assert!(out.contains("    - test     : jest\n"));
+    }
+
+    #[test]
+    fn test_no_truncate_overrides_max_width() {
+        control::set_override(false);
+        let scripts = vec![(
+            "build:production".to_string(),
+            "webpack --mode production --config webpack.prod.js".to_string(),
+        )];
+        let render = |args: &[&str]| {
+            let cli = Cli::parse_from(["sl"].iter().chain(args));
+            let mut out = Vec::new();
+            print_list(&mut out, &scripts, &cli).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let truncating = ["--max-width", "40", "--truncate-names", "8"];
+        assert_eq!(
+            render(&truncating),
+            "\n   build:p…: webpack --mode production …\n\n"
+        );
+        assert_eq!(
+            render(&[&truncating[..], &["--no-truncate"]].concat()),
+            "\n   build:production: webpack --mode production --config webpack.prod.js\n\n"
+        );
+        assert_eq!(render(&["--max-width", "0"]), render(&["--no-truncate"]));
+    }
+
+    #[test]
+    fn test_print_json() {
+        let scripts: Vec<_> = [
+            ("build", "tsc"),
+            ("say", "echo \"hi\"\nbye"),
+            ("test", "jest"),
+        ]
+        .iter()
+        .map(|(name, command)| (name.to_string(), command.to_string()))
+        .collect();
+        let render = |scripts: &[(String, String)]| {
+            let mut out = Vec::new();
+            print_json(&mut out, scripts).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        // What building the whole map and string first gave, in order
+        let map: serde_json::Map<_, _> = scripts
+            .iter()
+            .map(|(name, command)| (name.clone(), command.as_str().into()))
+            .collect();
+        let mut expected = String::from("\n");
+        for line in serde_json::to_string_pretty(&map).unwrap().lines() {
+            expected.push_str(&format!("   {}\n", line));
+        }
+        expected.push('\n');
+        assert_eq!(render(&scripts), expected);
+        assert_eq!(render(&[]), "\n   {}\n\n");
+    }
+
+    // cargo test --release bench_print_json -- --ignored --nocapture
+    #[test]
+    #[ignore]
+    fn bench_print_json() {
+        let scripts: Vec<_> = (0..200_000)
+            .map(|i| {
+                let command = format!("node scripts/{}.js --flag", i);
+                (format!("script:{}", i), command)
+            })
+            .collect();
+        let started = std::time::Instant::now();
+        let mut out = Vec::new();
+        print_json(&mut out, &scripts).unwrap();
+        println!(
+            "{} scripts, {} bytes in {:?}",
+            scripts.len(),
+            out.len(),
+            started.elapsed()
+        );
+    }
+
+    #[test]
+    fn test_json_fields() {
+        let package: PackageJson = serde_json::from_str(
+            r#"{
+                "scripts": { "build": "tsc", "ci": "npm run build && npm test", "test": "jest" },
+                "scripts-info": { "ci": "Everything CI runs" }
+            }"#,
+        )
+        .unwrap();
+        let scripts = vec![("ci".to_string(), package.scripts["ci"].clone())];
+
+        let package_path = Path::new("package.json");
+        let json = json_fields(
+            package_path,
+            &package,
+            &scripts,
+            &[Field::Name, Field::Description, Field::References],
+        )
+        .unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{
+                "name": "ci",
+                "description": "Everything CI runs",
+                "references": ["build", "test"]
+            }])
+        );
+
+        let scripts = vec![("build".to_string(), package.scripts["build"].clone())];
+        let fields = [Field::Command, Field::Description];
+        let json = json_fields(package_path, &package, &scripts, &fields).unwrap();
+        assert_eq!(
+            json,
+            serde_json::json!([{ "command": "tsc", "description": null }])
+        );
+    }
+
+    #[test]
+    fn test_json_fields_path_and_package_manager() {
+        let dir = tempfile::tempdir().unwrap();
+        let package_path = dir.path().join("package.json");
+        fs::write(&package_path, r#"{ "scripts": { "build": "tsc" } }"#).unwrap();
+        fs::write(dir.path().join("pnpm-lock.yaml"), "").unwrap();
+        let package = read_package_json(&package_path).unwrap();
+        let scripts = vec![("build".to_string(), "tsc".to_string())];
+
+        let fields = [Field::Name, Field::Path, Field::PackageManager];
+        let json = json_fields(&package_path, &package, &scripts, &fields).unwrap();
+        let path = json[0]["path"].as_str().unwrap();
+        assert!(path.ends_with("/package.json"));
+        assert!(Path::new(path).is_absolute());
+        assert!(!path.contains('\\'));
+        assert_eq!(json[0]["packageManager"], "pnpm");
+    }
+
+    #[test]
+    fn test_use_script_info_key() {
+        let read = |key| {
+            let mut package: PackageJson = serde_json::from_str(
+                r#"{
+                    "scripts": { "build": "tsc" },
+                    "scripts-info": { "build": "From scripts-info" },
+                    "scriptsComments": { "build": "From scriptsComments" },
+                    "scriptDescriptions": ["not", "a", "map"]
+                }"#,
+            )
+            .unwrap();
+            use_script_info_key(&mut package, key).map(|()| package.scripts_info)
+        };
+
+        assert_eq!(read("scripts-info").unwrap()["build"], "From scripts-info");
+        assert_eq!(
+            read("scriptsComments").unwrap()["build"],
+            "From scriptsComments"
+        );
+        assert!(read("scriptsDocs").unwrap().is_empty());
+        assert!(read("scriptDescriptions").is_err());
+    }
+
+    #[test]
+    fn test_hide_long_commands() {
+        let mut scripts = vec![
+            ("exact".to_string(), "x".repeat(80)),
+            ("long".to_string(), "x".repeat(81)),
+            ("short".to_string(), "tsc".to_string()),
+        ];
+
+        assert_eq!(hide_long_commands(&mut scripts, 80), 1);
+        let names: Vec<_> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["exact", "short"]);
+
+        assert_eq!(hide_long_commands(&mut scripts, 79), 1);
+        assert_eq!(scripts.len(), 1);
+    }
+
+    #[test]
+    fn test_hidden_count_in_footer() {
+        control::set_override(false);
+        let scripts = vec![("build".to_string(), "tsc".to_string())];
+        let cli = Cli::parse_from(["sl", "--hide-long-commands"]);
+        assert_eq!(cli.hide_long_commands, Some(80));
+
+        let mut out = Vec::new();
+        print_scripts(
+            &mut out,
+            &PackageJson::default(),
+            &scripts,
+            2,
+            &Annotations::default(),
+            &cli,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.ends_with("   2 script(s) hidden with commands over 80 characters\n\n"));
+    }
+
+    #[test]
+    fn test_get_script() {
+        let scripts = vec![
+            ("build".to_string(), "tsc -p .".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        assert_eq!(get_script(&scripts, "build"), Some("tsc -p ."));
+        assert_eq!(get_script(&scripts, "deploy"), None);
+        assert_eq!(get_script(&scripts, "Build"), None);
+    }
+
+    #[test]
+    fn test_mark_runnable() {
+        control::set_override(false);
+        let scripts = vec![
+            ("build".to_string(), "tsc".to_string()),
+            ("deploy".to_string(), "./deploy.sh $1".to_string()),
+        ];
+        let cli = Cli::parse_from(["sl", "--mark-runnable"]);
+
+        let mut out = Vec::new();
+        print_scripts(
+            &mut out,
+            &PackageJson::default(),
+            &scripts,
+            0,
+            &Annotations::default(),
+            &cli,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("    - build  : tsc\n"));
+        assert!(out.contains("    - deploy : ./deploy.sh $1  [args]\n"));
+    }
+
+    #[test]
+    fn test_watch_interval_must_be_positive() {
+        let parse = |interval| Cli::try_parse_from(["sl", "--watch", "--watch-interval", interval]);
+        assert!(parse("0").is_err());
+        let interval = parse("250").ok().and_then(|cli| cli.watch_interval);
+        assert_eq!(interval, Some(250));
+    }
+
+    #[test]
+    fn test_auto_workspaces() {
+        let dir = tempfile::tempdir().unwrap();
+        let package_path = dir.path().join("package.json");
+        fs::write(&package_path, r#"{ "scripts": { "lint": "eslint ." } }"#).unwrap();
+        let path = package_path.to_str().unwrap();
+        let auto = |args: &[&str]| {
+            let cli = parse_cli(["sl", "--path", path].iter().chain(args));
+            auto_workspaces(&cli, &package_path).unwrap()
+        };
+        assert!(!auto(&[]));
+
+        let pnpm_workspace = dir.path().join(workspaces::PNPM_WORKSPACE_FILE);
+        fs::write(pnpm_workspace, "packages: []\n").unwrap();
+        assert!(auto(&[]));
+        assert!(auto(&["--filter", "li", "--sort-by", "none"]));
+        assert!(!auto(&["--no-auto-workspaces"]));
+        // The workspace listing has no summaries, hidden counts or diff
+        assert!(!auto(&["--summarize", "--hide-long-commands", "10"]));
+        assert!(!auto(&["--diff", path]));
+        assert!(!auto(&["--format", "json"]));
+    }
+
+    #[test]
+    fn test_print_workspaces_empty_modes() {
+        control::set_override(false);
+        let members = vec![
+            (
+                "app".to_string(),
+                vec![("build".to_string(), "tsc".to_string())],
+            ),
+            ("docs".to_string(), Vec::new()),
+        ];
+        let render = |empty: &str| {
+            let cli = Cli::parse_from(["sl", "--workspaces", "--empty", empty]);
+            let mut out = Vec::new();
+            print_workspaces(&mut out, &members, &cli).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        let skip = render("skip");
+        assert_eq!(skip, "\n   app\n\n    - build : tsc\n\n");
+
+        let show = render("show");
+        assert!(show.ends_with("\n   docs\n\n    (no scripts)\n\n"));
+
+        let warn = render("warn");
+        assert!(warn.starts_with(&skip));
+        assert!(warn.ends_with("   ⚠️  No scripts\n\n    - docs\n\n"));
+    }
+
+    #[test]
+    fn test_ascii_only_keeps_borders_aligned() {
+        control::set_override(false);
+        let scripts = vec![
+            ("démarrer".to_string(), "echo → ok".to_string()),
+            ("x".to_string(), "y".to_string()),
+        ];
+        let cli = Cli::parse_from(["sl", "--ascii-only", "--table-style", "ascii"]);
+
+        let mut out = Vec::new();
+        print_section(&mut out, "app", &scripts, &Annotations::default(), &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   app\n\n\
+             \x20  +-------------------------+\n\
+             \x20  | - demarrer : echo -> ok |\n\
+             \x20  | - x        : y          |\n\
+             \x20  +-------------------------+\n\n"
+        );
+    }
+
+    #[test]
+    fn test_show_command_hash() {
+        control::set_override(false);
+        let scripts = vec![
+            ("build".to_string(), "tsc".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let cli = Cli::parse_from(["sl", "--show-command-hash", "--max-width", "30"]);
+
+        let mut out = Vec::new();
+        print_section(&mut out, "app", &scripts, &Annotations::default(), &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            format!(
+                "\n   app\n\n    - build : {}  tsc\n    - test  : {}  jest\n\n",
+                changes::command_hash("tsc"),
+                changes::command_hash("jest")
+            )
+        );
+    }
+
+    #[test]
+    fn test_list_formats() {
+        let formats = list_formats();
+        let lines: Vec<_> = formats.lines().filter(|line| !line.is_empty()).collect();
+        assert_eq!(lines.len(), OutputFormat::value_variants().len());
+        assert_eq!(
+            lines[0],
+            "   table                     Aligned names and commands under the package name"
+        );
+        assert!(formats.contains("\n   jira-wiki                 Jira wiki markup table\n"));
+    }
+
+    #[test]
+    fn test_commands_only() {
+        let scripts = vec![
+            ("build".to_string(), "tsc -p .".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let cli = Cli::parse_from(["sl", "--commands-only"]);
+
+        let mut out = Vec::new();
+        print_scripts(
+            &mut out,
+            &PackageJson::default(),
+            &scripts,
+            0,
+            &Annotations::default(),
+            &cli,
+        )
+        .unwrap();
+        assert_eq!(String::from_utf8(out).unwrap(), "tsc -p .\njest\n");
+
+        let mut out = Vec::new();
+        print_list(&mut out, &scripts, &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   tsc -p .\n   jest\n\n"
+        );
+        assert!(Cli::try_parse_from(["sl", "--commands-only", "-n"]).is_err());
+    }
+
+    #[test]
+    fn test_env_flags() {
+        let cli = Cli::parse_from([
+            "sl",
+            "--run",
+            "build",
+            "--env",
+            "NODE_ENV=production",
+            "--env",
+            "FLAGS=a=b c",
+        ]);
+        assert_eq!(
+            cli.env,
+            [
+                ("NODE_ENV".to_string(), "production".to_string()),
+                ("FLAGS".to_string(), "a=b c".to_string()),
+            ]
+        );
+        assert!(Cli::try_parse_from(["sl", "--run", "build", "--env", "NODE_ENV"]).is_err());
+        assert!(Cli::try_parse_from(["sl", "--run", "build", "--env", "1X=y"]).is_err());
+    }
+
+    #[test]
+    fn test_skip_private() {
+        let package: PackageJson = serde_json::from_str(r#"{ "private": true }"#).unwrap();
+        assert!(package.private);
+        assert!(!PackageJson::default().private);
+
+        let cli = Cli::parse_from(["sl", "--workspaces", "--no-private"]);
+        assert!(cli.skip_private);
+        assert!(Cli::try_parse_from(["sl", "--skip-private"]).is_err());
+    }
+
+    #[test]
+    fn test_color_override_for_file_output() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("scripts.txt");
+        let file = file.to_str().unwrap();
+        let color = |args: &[&str]| {
+            let cli = Cli::parse_from([&["sl"], args].concat());
+            color_override(&cli)
+        };
+
+        assert_eq!(color(&[]), None);
+        assert_eq!(color(&["--color", "always"]), Some(true));
+        assert_eq!(color(&["--color", "never"]), Some(false));
+        assert_eq!(color(&["--output", file]), Some(false));
+        assert_eq!(color(&["--color", "always", "--output", file]), Some(false));
+        assert_eq!(
+            color(&["--color", "always", "--output", file, "--force-color-file"]),
+            Some(true)
+        );
+        #[cfg(unix)]
+        assert_eq!(
+            color(&["--color", "always", "--output", "/dev/null"]),
+            Some(true)
+        );
+    }
+
+    #[test]
+    fn test_print_binaries() {
+        control::set_override(false);
+        let bins = ["eslint".to_string(), "tsc".to_string()];
+        let scripts = [("build".to_string(), "tsc".to_string())];
+
+        let mut out = Vec::new();
+        print_binaries(&mut out, &bins, &scripts).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "   Available binaries\n\n    - eslint\n    - tsc (used in scripts)\n\n"
+        );
+
+        let mut out = Vec::new();
+        print_binaries(&mut out, &[], &scripts).unwrap();
+        assert!(out.is_empty());
+    }
+
+    #[test]
+    fn test_highlight_changed_sigils() {
+        control::set_override(false);
+        let old = vec![
+            ("build".to_string(), "tsc".to_string()),
+            ("e2e".to_string(), "cypress run".to_string()),
+            ("lint".to_string(), "eslint .".to_string()),
+        ];
+        let scripts = vec![
+            ("build".to_string(), "tsc -b".to_string()),
+            ("lint".to_string(), "eslint .".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let changes = changes::diff_with_previous(&scripts, &old);
+        let cli = Cli::parse_from(["sl", "--highlight-changed"]);
+
+        let mut out = Vec::new();
+        let annotations = Annotations {
+            changes: Some(&changes),
+            ..Default::default()
+        };
+        print_section(&mut out, "app", &scripts, &annotations, &cli).unwrap();
+        print_removed(&mut out, &changes.removed).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   app\n\n    ~ build : tsc -b\n    - lint  : eslint .\n    + test  : jest\n\n\
+             \x20  Removed\n\n    - e2e : cypress run\n\n"
+        );
+    }
+
+    #[test]
+    fn test_merge_shows_overridden_commands() {
+        control::set_override(false);
+        let dir = tempfile::tempdir().unwrap();
+        let overlay = dir.path().join("overrides.json");
+        fs::write(
+            &overlay,
+            r#"{ "scripts": { "build": "tsc -p tsconfig.prod.json", "lint": "eslint .", "ci": "npm test" } }"#,
+        )
+        .unwrap();
+        let mut package: PackageJson = serde_json::from_str(
+            r#"{ "scripts": { "build": "tsc", "lint": "eslint .", "test": "jest" } }"#,
+        )
+        .unwrap();
+
+        let overridden = merge_scripts(&mut package, &overlay).unwrap();
+        assert_eq!(overridden, HashMap::from([("build".into(), "tsc".into())]));
+        assert_eq!(package.scripts["ci"], "npm test");
+        assert_eq!(package.scripts.len(), 4);
+
+        let mut scripts: Vec<_> = package.scripts.into_iter().collect();
+        scripts.sort();
+        let annotations = Annotations {
+            overridden: Some(&overridden),
+            ..Default::default()
+        };
+        let cli = Cli::parse_from(["sl"]);
+        let mut out = Vec::new();
+        print_section(&mut out, "app", &scripts, &annotations, &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   app\n\n\
+             \x20   - build : tsc -p tsconfig.prod.json\n\
+             \x20             (was: tsc)\n\
+             \x20   - ci    : npm test\n\
+             \x20   - lint  : eslint .\n\
+             \x20   - test  : jest\n\n"
+        );
+    }
+
+    #[test]
+    fn test_found_footer() {
+        control::set_override(false);
+        let scripts: Vec<_> = (0..1234)
+            .map(|i| (format!("gen:{}", i), "node gen.js".to_string()))
+            .collect();
+
+        let cli = Cli::parse_from(["sl", "--plain"]);
+        let mut out = Vec::new();
+        print_scripts(
+            &mut out,
+            &PackageJson::default(),
+            &scripts,
+            0,
+            &Annotations::default(),
+            &cli,
+        )
+        .unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.ends_with("\n   Found 1234 script(s)\n\n"));
+
+        // Whatever the locale groups with, the digits stay the same
+        let cli = Cli::parse_from(["sl"]);
+        let formatted = format_count(1234, &cli);
+        let digits: String = formatted.chars().filter(char::is_ascii_digit).collect();
+        assert_eq!(digits, "1234");
+    }
+
+    #[test]
+    fn test_summary_column() {
+        control::set_override(false);
+        let scripts = vec![
+            ("build".to_string(), "webpack --mode production".to_string()),
+            ("release".to_string(), "node release.js".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let summaries = HashMap::from([
+            ("build".to_string(), "Production webpack build".to_string()),
+            ("test".to_string(), "Run Jest tests".to_string()),
+        ]);
+        let annotations = Annotations {
+            summaries: Some(&summaries),
+            ..Default::default()
+        };
+        let cli = Cli::parse_from(["sl", "--summarize"]);
+
+        let mut out = Vec::new();
+        print_section(&mut out, "app", &scripts, &annotations, &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   app\n\n\
+             \x20   - build   : webpack --mode production  Production webpack build\n\
+             \x20   - release : node release.js\n\
+             \x20   - test    : jest                       Run Jest tests\n\n"
+        );
+    }
+
+    #[test]
+    fn test_invocation_column() {
+        control::set_override(false);
+        let scripts = vec![
+            ("build".to_string(), "webpack".to_string()),
+            ("test:unit".to_string(), "jest".to_string()),
+        ];
+        let invocations = HashMap::from([
+            ("build".to_string(), "pnpm run build".to_string()),
+            ("test:unit".to_string(), "pnpm run test:unit".to_string()),
+        ]);
+        let summaries = HashMap::from([("build".to_string(), "Bundle the app".to_string())]);
+        let annotations = Annotations {
+            summaries: Some(&summaries),
+            invocations: Some(&invocations),
+            ..Default::default()
+        };
+        let cli = Cli::parse_from(["sl", "--print-pm-command"]);
+
+        let mut out = Vec::new();
+        print_section(&mut out, "app", &scripts, &annotations, &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   app\n\n\
+             \x20   - build     : webpack  pnpm run build      Bundle the app\n\
+             \x20   - test:unit : jest     pnpm run test:unit\n\n"
+        );
+    }
+
+    #[test]
+    fn test_mark_matches() {
+        let search = search_regex("Web");
+        let bracket = |text: &str| format!("[{}]", text);
+        assert_eq!(
+            mark_matches("webpack --config web.js", &search, bracket).as_deref(),
+            Some("\x1b[1;32mweb\x1b[0m[pack --config ]\x1b[1;32mweb\x1b[0m[.js]")
+        );
+        assert_eq!(mark_matches("tsc", &search, bracket), None);
+        // Taken literally, not as a regex
+        assert_eq!(
+            mark_matches("a.b", &search_regex("."), bracket).as_deref(),
+            Some("[a]\x1b[1;32m.\x1b[0m[b]")
+        );
+    }
+
+    #[test]
+    fn test_filter_by_command() {
+        let scripts = vec![
+            ("build".to_string(), "webpack --mode production".to_string()),
+            ("dev".to_string(), "webpack serve".to_string()),
+            (
+                "build:types".to_string(),
+                "tsc --emitDeclarationOnly".to_string(),
+            ),
+            ("lint".to_string(), "eslint .".to_string()),
+        ];
+        let names = |scripts: &[(String, String)]| -> Vec<String> {
+            scripts.iter().map(|(name, _)| name.clone()).collect()
+        };
+
+        let mut exact = scripts.clone();
+        filter_by_command(&mut exact, "eslint .");
+        assert_eq!(names(&exact), ["lint"]);
+
+        let mut partial = scripts.clone();
+        filter_by_command(&mut partial, "WebPack");
+        assert_eq!(names(&partial), ["build", "dev"]);
+
+        let mut searched = scripts.clone();
+        apply_filters(&Cli::parse_from(["sl", "--search", "BUILD"]), &mut searched);
+        assert_eq!(names(&searched), ["build", "build:types"]);
+        let mut searched = scripts.clone();
+        let cli = Cli::parse_from(["sl", "--search", "declaration"]);
+        apply_filters(&cli, &mut searched);
+        assert_eq!(names(&searched), ["build:types"]);
+
+        let mut combined = scripts.clone();
+        let cli = Cli::parse_from(["sl", "--filter", "build", "--filter-by-command", "webpack"]);
+        apply_filters(&cli, &mut combined);
+        assert_eq!(names(&combined), ["build"]);
+    }
+
+    #[test]
+    fn test_table_style_frames_rows() {
+        control::set_override(false);
+        let scripts = vec![
+            ("build".to_string(), "tsc".to_string()),
+            ("test".to_string(), "jest".to_string()),
+        ];
+        let cli = Cli::parse_from(["sl", "--table-style", "unicode"]);
+
+        let mut out = Vec::new();
+        print_section(&mut out, "app", &scripts, &Annotations::default(), &cli).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   app\n\n\
+             \x20  ┌────────────────┐\n\
+             \x20  │ - build : tsc  │\n\
+             \x20  │ - test  : jest │\n\
+             \x20  └────────────────┘\n\n"
+        );
     }
 }
@@ -0,0 +1,87 @@
+use std::fs;
+use std::path::Path;
+
+/// A setting read from an .npmrc and the file it came from
+#[derive(Debug, PartialEq, Eq)]
+pub struct Setting {
+    pub value: String,
+    pub source: String,
+}
+
+/// The `script-shell` npm will use for the package in `package_dir`: the
+/// project .npmrc wins over the user one in `home`, as it does for npm
+pub fn script_shell(package_dir: &Path, home: Option<&Path>) -> Option<Setting> {
+    let candidates = [Some(package_dir), home];
+    candidates.into_iter().flatten().find_map(|dir| {
+        let path = dir.join(".npmrc");
+        let content = fs::read_to_string(&path).ok()?;
+        get(&content, "script-shell").map(|value| Setting {
+            value,
+            source: path.display().to_string(),
+        })
+    })
+}
+
+/// Value of `key` in .npmrc `content`; the last assignment wins
+fn get(content: &str, key: &str) -> Option<String> {
+    content
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.starts_with(['#', ';']))
+        .filter_map(|line| line.split_once('='))
+        .filter(|(k, _)| k.trim() == key)
+        .map(|(_, value)| unquote(value.trim()).to_string())
+        .next_back()
+}
+
+fn unquote(value: &str) -> &str {
+    ['"', '\'']
+        .iter()
+        .find_map(|&q| value.strip_prefix(q).and_then(|v| v.strip_suffix(q)))
+        .unwrap_or(value)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get() {
+        let content = "# comment\n; script-shell=/bin/nope\nregistry=https://example.com/\n\
+                       script-shell = /bin/zsh\nscript-shell=\"/usr/bin/bash\"\n";
+        assert_eq!(
+            get(content, "script-shell").as_deref(),
+            Some("/usr/bin/bash")
+        );
+        assert_eq!(
+            get(content, "registry").as_deref(),
+            Some("https://example.com/")
+        );
+        assert_eq!(get(content, "cache"), None);
+    }
+
+    #[test]
+    fn test_script_shell_prefers_project_npmrc() {
+        let project = tempfile::tempdir().unwrap();
+        let home = tempfile::tempdir().unwrap();
+        fs::write(home.path().join(".npmrc"), "script-shell=/bin/zsh\n").unwrap();
+
+        let setting = script_shell(project.path(), Some(home.path())).unwrap();
+        assert_eq!(setting.value, "/bin/zsh");
+
+        fs::write(project.path().join(".npmrc"), "script-shell=/bin/bash\n").unwrap();
+        let setting = script_shell(project.path(), Some(home.path())).unwrap();
+        assert_eq!(setting.value, "/bin/bash");
+        assert_eq!(
+            setting.source,
+            project.path().join(".npmrc").display().to_string()
+        );
+
+        assert_eq!(
+            script_shell(project.path(), None).unwrap().value,
+            "/bin/bash"
+        );
+        fs::remove_file(project.path().join(".npmrc")).unwrap();
+        assert_eq!(script_shell(project.path(), None), None);
+    }
+}
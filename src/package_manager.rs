@@ -0,0 +1,174 @@
+use anyhow::{Context, Result};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::shell;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PackageManager {
+    Npm,
+    Yarn,
+    Pnpm,
+    Bun,
+}
+
+impl PackageManager {
+    /// The package manager whose lockfile is in `dir`, npm if there isn't one
+    pub fn detect(dir: &Path) -> Self {
+        detect_lockfile(dir)
+            .map(|lockfile| lockfile.manager)
+            .unwrap_or(PackageManager::Npm)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            PackageManager::Npm => "npm",
+            PackageManager::Yarn => "yarn",
+            PackageManager::Pnpm => "pnpm",
+            PackageManager::Bun => "bun",
+        }
+    }
+
+    /// Arguments that make this package manager run `script` with `args`
+    pub fn run_args(self, script: &str, args: &[String]) -> Vec<String> {
+        let mut run_args = vec!["run".to_string(), script.to_string()];
+        if !args.is_empty() {
+            // npm needs `--` to stop treating the args as its own options
+            if self == PackageManager::Npm {
+                run_args.push("--".to_string());
+            }
+            run_args.extend(args.iter().cloned());
+        }
+        run_args
+    }
+
+    /// How to run `script` from a shell, `npm run build`, quoted as needed
+    pub fn command_line(self, script: &str) -> String {
+        std::iter::once(self.name().to_string())
+            .chain(self.run_args(script, &[]))
+            .map(|word| shell::quote(&word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+}
+
+/// A lockfile found next to package.json
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Lockfile {
+    pub manager: PackageManager,
+    pub path: PathBuf,
+}
+
+impl Lockfile {
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|n| n.to_string_lossy().to_string())
+            .unwrap_or_default()
+    }
+}
+
+// Checked in order, so a repo with several lockfiles resolves to the first one
+const LOCKFILES: &[(&str, PackageManager)] = &[
+    ("package-lock.json", PackageManager::Npm),
+    ("npm-shrinkwrap.json", PackageManager::Npm),
+    ("yarn.lock", PackageManager::Yarn),
+    ("pnpm-lock.yaml", PackageManager::Pnpm),
+    ("bun.lockb", PackageManager::Bun),
+    ("bun.lock", PackageManager::Bun),
+];
+
+/// Find the lockfile in `dir`, if any
+pub fn detect_lockfile(dir: &Path) -> Option<Lockfile> {
+    LOCKFILES.iter().find_map(|(file, manager)| {
+        let path = dir.join(file);
+        path.is_file().then_some(Lockfile {
+            manager: *manager,
+            path,
+        })
+    })
+}
+
+/// Whether package.json was modified after the lockfile, meaning an install
+/// is probably needed
+pub fn is_stale(package_path: &Path, lockfile: &Lockfile) -> Result<bool> {
+    let modified = |path: &Path| {
+        fs::metadata(path)
+            .and_then(|m| m.modified())
+            .with_context(|| format!("Failed to read modification time of {}", path.display()))
+    };
+    Ok(modified(package_path)? > modified(&lockfile.path)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, SystemTime};
+
+    #[test]
+    fn test_detect_lockfile() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(detect_lockfile(dir.path()), None);
+
+        fs::write(dir.path().join("yarn.lock"), "").unwrap();
+        let lockfile = detect_lockfile(dir.path()).unwrap();
+        assert_eq!(lockfile.manager, PackageManager::Yarn);
+
+        fs::write(dir.path().join("package-lock.json"), "{}").unwrap();
+        let lockfile = detect_lockfile(dir.path()).unwrap();
+        assert_eq!(lockfile.manager, PackageManager::Npm);
+        assert_eq!(lockfile.path, dir.path().join("package-lock.json"));
+    }
+
+    #[test]
+    fn test_is_stale() {
+        let dir = tempfile::tempdir().unwrap();
+        let package_path = dir.path().join("package.json");
+        let lock_path = dir.path().join("package-lock.json");
+        fs::write(&package_path, "{}").unwrap();
+        fs::write(&lock_path, "{}").unwrap();
+        let lockfile = detect_lockfile(dir.path()).unwrap();
+
+        let now = SystemTime::now();
+        let set_mtime = |path: &Path, time: SystemTime| {
+            fs::File::options()
+                .write(true)
+                .open(path)
+                .unwrap()
+                .set_modified(time)
+                .unwrap();
+        };
+
+        set_mtime(&package_path, now - Duration::from_secs(60));
+        set_mtime(&lock_path, now);
+        assert!(!is_stale(&package_path, &lockfile).unwrap());
+
+        set_mtime(&package_path, now);
+        set_mtime(&lock_path, now - Duration::from_secs(60));
+        assert!(is_stale(&package_path, &lockfile).unwrap());
+    }
+
+    #[test]
+    fn test_run_args() {
+        let args = vec!["--watch".to_string()];
+        assert_eq!(PackageManager::Npm.run_args("test", &[]), ["run", "test"]);
+        assert_eq!(
+            PackageManager::Npm.run_args("test", &args),
+            ["run", "test", "--", "--watch"]
+        );
+        assert_eq!(
+            PackageManager::Pnpm.run_args("test", &args),
+            ["run", "test", "--watch"]
+        );
+    }
+
+    #[test]
+    fn test_command_line() {
+        assert_eq!(PackageManager::Npm.command_line("build"), "npm run build");
+        assert_eq!(
+            PackageManager::Yarn.command_line("test:unit"),
+            "yarn run test:unit"
+        );
+        assert_eq!(PackageManager::Bun.command_line("a b"), "bun run 'a b'");
+    }
+}
@@ -0,0 +1,142 @@
+use anyhow::{Context, Result};
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::terminal;
+use std::env;
+use std::io::{self, IsTerminal, Write};
+use std::process::{Command, Stdio};
+
+const PROMPT: &str = "\x1b[7m-- More -- (space: page, enter: line, q: quit)\x1b[0m";
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Key {
+    Page,
+    Line,
+    Quit,
+}
+
+/// Show `buf` a screen at a time when it doesn't fit in the terminal: through
+/// `$PAGER` if set, otherwise with the built-in pager. Output that fits, or
+/// that isn't going to a terminal, is written straight to stdout.
+pub fn page(buf: &[u8]) -> Result<()> {
+    let height = match terminal::size() {
+        Ok((_, rows)) if io::stdout().is_terminal() => rows as usize,
+        _ => return crate::write_stdout(buf),
+    };
+    let text = String::from_utf8_lossy(buf);
+    if text.lines().count() < height {
+        return crate::write_stdout(buf);
+    }
+
+    if let Some(pager) = env::var("PAGER").ok().filter(|p| !p.trim().is_empty()) {
+        // A pager that can't be started falls through to the built-in one
+        if external(&pager, buf).is_ok() {
+            return Ok(());
+        }
+    }
+
+    let lines: Vec<&str> = text.lines().collect();
+    terminal::enable_raw_mode().context("Failed to set up the terminal for paging")?;
+    let result = builtin(&lines, height, read_keys(), &mut io::stdout());
+    terminal::disable_raw_mode()?;
+    result.or_else(|e| match e.kind() {
+        io::ErrorKind::BrokenPipe => Ok(()),
+        _ => Err(e.into()),
+    })
+}
+
+fn external(pager: &str, buf: &[u8]) -> Result<()> {
+    let mut words = pager.split_whitespace();
+    let program = words.next().unwrap_or_default();
+    let mut child = Command::new(program)
+        .args(words)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    // The pager quitting early closes its stdin; that's not an error
+    let _ = child.stdin.take().unwrap().write_all(buf);
+    child.wait()?;
+    Ok(())
+}
+
+/// Write `lines` a screen of `height` rows at a time, waiting on `keys`
+/// between screens. Stops at the end or when the keys run out or say quit.
+fn builtin(
+    lines: &[&str],
+    height: usize,
+    mut keys: impl Iterator<Item = Key>,
+    out: &mut impl Write,
+) -> io::Result<()> {
+    // Leave the last row for the prompt
+    let page = height.saturating_sub(1).max(1);
+    let mut shown = 0;
+    let mut step = page;
+    while shown < lines.len() {
+        let end = (shown + step).min(lines.len());
+        for line in &lines[shown..end] {
+            // Raw mode doesn't translate "\n", so return the cursor by hand
+            write!(out, "{}\r\n", line)?;
+        }
+        shown = end;
+        if shown == lines.len() {
+            break;
+        }
+
+        write!(out, "{}", PROMPT)?;
+        out.flush()?;
+        let key = keys.next();
+        write!(out, "\r\x1b[K")?;
+        step = match key {
+            Some(Key::Page) => page,
+            Some(Key::Line) => 1,
+            Some(Key::Quit) | None => break,
+        };
+    }
+    out.flush()
+}
+
+// Keys from the terminal, mapped to pager actions; anything else is ignored
+fn read_keys() -> impl Iterator<Item = Key> {
+    std::iter::from_fn(|| loop {
+        let Ok(Event::Key(key)) = event::read() else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        return Some(match key.code {
+            KeyCode::Char(' ') | KeyCode::PageDown => Key::Page,
+            KeyCode::Enter | KeyCode::Down | KeyCode::Char('j') => Key::Line,
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => Key::Quit,
+            KeyCode::Char('q') | KeyCode::Esc => Key::Quit,
+            _ => continue,
+        });
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn shown(lines: &[&str], height: usize, keys: &[Key]) -> Vec<String> {
+        let mut out = Vec::new();
+        builtin(lines, height, keys.iter().copied(), &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        out.split("\r\n")
+            .map(|line| line.rsplit("\r\x1b[K").next().unwrap().to_string())
+            .filter(|line| !line.is_empty())
+            .collect()
+    }
+
+    #[test]
+    fn test_builtin_pager() {
+        let lines = ["1", "2", "3", "4", "5", "6", "7"];
+
+        assert_eq!(shown(&lines, 4, &[Key::Quit]), ["1", "2", "3"]);
+        assert_eq!(
+            shown(&lines, 4, &[Key::Line, Key::Quit]),
+            ["1", "2", "3", "4"]
+        );
+        assert_eq!(shown(&lines, 4, &[Key::Page, Key::Page]), lines);
+        assert_eq!(shown(&lines, 4, &[]), ["1", "2", "3"]);
+        assert_eq!(shown(&lines, 20, &[]), lines);
+    }
+}
@@ -0,0 +1,166 @@
+use anyhow::Result;
+use crossterm::event::{self, Event, KeyCode, KeyEventKind, KeyModifiers};
+use crossterm::{cursor, execute, queue, terminal};
+use std::io::{self, Write};
+
+use crate::color::*;
+
+/// Char indices of `name` matching `query` as a case-insensitive
+/// subsequence (`bld` matches "build"), or `None` if it doesn't match
+pub fn fuzzy_match(query: &str, name: &str) -> Option<Vec<usize>> {
+    let mut positions = Vec::new();
+    let mut chars = name.chars().enumerate();
+    for q in query.chars().flat_map(char::to_lowercase) {
+        let (i, _) = chars
+            .by_ref()
+            .find(|(_, c)| c.to_lowercase().eq(std::iter::once(q)))?;
+        positions.push(i);
+    }
+    Some(positions)
+}
+
+/// `name` with the characters at `positions` highlighted: green and bold,
+/// or just bold when colors are turned off
+pub fn highlight(name: &str, positions: &[usize]) -> String {
+    let colorize = control::SHOULD_COLORIZE.should_colorize();
+    name.chars()
+        .enumerate()
+        .map(|(i, c)| match positions.contains(&i) {
+            false => c.to_string(),
+            true if colorize => c.to_string().green().bold().to_string(),
+            true => format!("\x1b[1m{}\x1b[22m", c),
+        })
+        .collect()
+}
+
+/// Scripts matching `query` with the matched positions, best first: the
+/// fewer gaps between matched characters, the better
+fn matches<'a>(query: &str, scripts: &'a [(String, String)]) -> Vec<(&'a str, Vec<usize>)> {
+    let mut matches: Vec<_> = scripts
+        .iter()
+        .filter_map(|(name, _)| fuzzy_match(query, name).map(|p| (name.as_str(), p)))
+        .collect();
+    let spread = |positions: &[usize]| match (positions.first(), positions.last()) {
+        (Some(first), Some(last)) => last - first,
+        _ => 0,
+    };
+    matches.sort_by_key(|(_, positions)| spread(positions));
+    matches
+}
+
+/// Let the user pick a script by typing part of its name. Returns the picked
+/// name, or `None` if they backed out with Esc or Ctrl-C.
+pub fn pick(scripts: &[(String, String)]) -> Result<Option<String>> {
+    let mut stdout = io::stdout();
+    terminal::enable_raw_mode()?;
+    execute!(stdout, terminal::EnterAlternateScreen)?;
+    let picked = run(scripts, &mut stdout);
+    execute!(stdout, terminal::LeaveAlternateScreen)?;
+    terminal::disable_raw_mode()?;
+    picked
+}
+
+fn run(scripts: &[(String, String)], out: &mut impl Write) -> Result<Option<String>> {
+    let mut query = String::new();
+    let mut selected = 0;
+    loop {
+        let found = matches(&query, scripts);
+        selected = selected.min(found.len().saturating_sub(1));
+        let (_, rows) = terminal::size()?;
+        render(
+            out,
+            &query,
+            &found,
+            selected,
+            rows.saturating_sub(1) as usize,
+        )?;
+
+        let Event::Key(key) = event::read()? else {
+            continue;
+        };
+        if key.kind != KeyEventKind::Press {
+            continue;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => return Ok(None),
+            KeyCode::Esc => return Ok(None),
+            KeyCode::Enter => return Ok(found.get(selected).map(|(name, _)| name.to_string())),
+            KeyCode::Up => selected = selected.saturating_sub(1),
+            KeyCode::Down => selected += 1,
+            KeyCode::Backspace => {
+                query.pop();
+            }
+            KeyCode::Char(c) => {
+                query.push(c);
+                selected = 0;
+            }
+            _ => {}
+        }
+    }
+}
+
+fn render(
+    out: &mut impl Write,
+    query: &str,
+    found: &[(&str, Vec<usize>)],
+    selected: usize,
+    max_rows: usize,
+) -> Result<()> {
+    queue!(
+        out,
+        cursor::MoveTo(0, 0),
+        terminal::Clear(terminal::ClearType::All)
+    )?;
+    // Raw mode doesn't translate "\n", so each line returns the cursor itself
+    write!(out, "{} {}\r\n", ">".green(), query)?;
+    for (i, (name, positions)) in found.iter().take(max_rows).enumerate() {
+        let pointer = if i == selected { ">" } else { " " };
+        write!(
+            out,
+            "{} {}\r\n",
+            pointer.green(),
+            highlight(name, positions)
+        )?;
+    }
+    queue!(out, cursor::MoveTo(2 + query.chars().count() as u16, 0))?;
+    out.flush()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_fuzzy_match() {
+        assert_eq!(fuzzy_match("bld", "build"), Some(vec![0, 3, 4]));
+        assert_eq!(fuzzy_match("TU", "test:unit"), Some(vec![0, 5]));
+        assert_eq!(fuzzy_match("", "lint"), Some(vec![]));
+        assert_eq!(fuzzy_match("dl", "build"), None);
+    }
+
+    #[test]
+    fn test_highlight_degrades_to_bold() {
+        control::set_override(false);
+        assert_eq!(
+            highlight("build", &[0, 3]),
+            "\x1b[1mb\x1b[22mui\x1b[1ml\x1b[22md"
+        );
+        assert_eq!(highlight("lint", &[]), "lint");
+    }
+
+    #[test]
+    fn test_matches_tightest_first() {
+        let scripts = [
+            ("lint:ts".to_string(), String::new()),
+            ("test:unit".to_string(), String::new()),
+            ("tsc".to_string(), String::new()),
+            ("build".to_string(), String::new()),
+        ];
+        let names: Vec<_> = matches("ts", &scripts)
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        assert_eq!(names, ["tsc", "test:unit", "lint:ts"]);
+    }
+}
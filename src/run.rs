@@ -0,0 +1,790 @@
+use anyhow::{bail, Context, Result};
+use std::env;
+use std::fmt;
+use std::io::{self, BufRead, IsTerminal, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, ExitStatus};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::color::*;
+use crate::package_manager::PackageManager;
+use crate::{config, destructive, envs, history, npmrc, shell, Cli, PackageJson};
+
+/// What `sl` exits with when `--timeout` kills a script, following GNU `timeout`
+pub const TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// The script was still running when `--timeout` ran out, and was killed
+#[derive(Debug)]
+pub struct TimedOut {
+    pub script: String,
+    pub seconds: u64,
+}
+
+impl fmt::Display for TimedOut {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Script `{}` timed out after {}s and was killed",
+            self.script, self.seconds
+        )
+    }
+}
+
+impl std::error::Error for TimedOut {}
+
+/// The script ran and exited unsuccessfully
+#[derive(Debug)]
+pub struct ScriptFailed {
+    pub script: String,
+    pub status: ExitStatus,
+}
+
+impl ScriptFailed {
+    /// The script's own exit code, or 128 plus the number of the signal that
+    /// killed it, the way shells report that
+    pub fn exit_code(&self) -> i32 {
+        if let Some(code) = self.status.code() {
+            return code;
+        }
+        #[cfg(unix)]
+        if let Some(signal) = std::os::unix::process::ExitStatusExt::signal(&self.status) {
+            return 128 + signal;
+        }
+        1
+    }
+}
+
+impl fmt::Display for ScriptFailed {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "Script `{}` failed ({})", self.script, self.status)
+    }
+}
+
+impl std::error::Error for ScriptFailed {}
+
+/// What `sl` should exit with for an error from `run_script`, when it's the
+/// script's doing rather than sl's: the script's exit code, or 124 after
+/// `--timeout`
+pub fn exit_code(error: &anyhow::Error) -> Option<i32> {
+    if let Some(failed) = error.downcast_ref::<ScriptFailed>() {
+        return Some(failed.exit_code());
+    }
+    error.is::<TimedOut>().then_some(TIMEOUT_EXIT_CODE)
+}
+
+/// Everything needed to start a script, worked out up front so that
+/// `--dry-run` can show exactly what would happen
+#[derive(Debug)]
+pub struct Invocation {
+    pub program: String,
+    pub args: Vec<String>,
+    pub envs: Vec<(String, String)>,
+    /// The shell the package manager will run the script with, and where
+    /// that choice came from
+    pub shell: Option<npmrc::Setting>,
+    /// Where it's started: the package's directory, so that relative paths
+    /// in the script resolve the same from anywhere
+    pub dir: PathBuf,
+}
+
+impl Invocation {
+    /// `shell` (from `--shell`) overrides any `script-shell` in .npmrc
+    pub fn new(package_path: &Path, script: &str, args: &[String], shell: Option<&str>) -> Self {
+        let dir = crate::package_dir(package_path);
+        let manager = PackageManager::detect(&dir);
+
+        let shell = match shell {
+            Some(shell) => Some(npmrc::Setting {
+                value: shell.to_string(),
+                source: "--shell".to_string(),
+            }),
+            None => {
+                let home = env::var_os("HOME").map(PathBuf::from);
+                npmrc::script_shell(&dir, home.as_deref())
+            }
+        };
+
+        // npm reads .npmrc from the directory it's started in, which --cwd can
+        // make other than the package's; the environment form always applies
+        let mut envs = Vec::new();
+        if let Some(shell) = &shell {
+            envs.push(("npm_config_script_shell".to_string(), shell.value.clone()));
+        }
+
+        Invocation {
+            program: manager.name().to_string(),
+            args: manager.run_args(script, args),
+            envs,
+            shell,
+            dir,
+        }
+    }
+
+    /// The command line, quoted for a POSIX shell
+    pub fn command_line(&self) -> String {
+        std::iter::once(&self.program)
+            .chain(&self.args)
+            .map(|word| shell::quote(word))
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// `command_line` behind the environment as `NAME=value` prefixes, after
+    /// a `cd` unless it starts in the current directory: something a POSIX
+    /// shell can run as is
+    pub fn shell_command(&self) -> String {
+        let mut words: Vec<String> = self
+            .envs
+            .iter()
+            .map(|(name, value)| format!("{}={}", name, shell::quote(value)))
+            .collect();
+        words.push(self.command_line());
+        let command = words.join(" ");
+        if self.dir == Path::new(".") {
+            command
+        } else {
+            let dir = shell::quote(&self.dir.to_string_lossy());
+            format!("cd {} && {}", dir, command)
+        }
+    }
+
+    pub fn print_dry_run(&self, out: &mut impl Write) -> std::io::Result<()> {
+        writeln!(out)?;
+        writeln!(out, "   {} {}", "Would run:".green(), self.command_line())?;
+        let shown = self
+            .envs
+            .iter()
+            .filter(|(name, _)| self.shell.is_none() || name != "npm_config_script_shell");
+        for (i, (name, value)) in shown.enumerate() {
+            let label = if i == 0 { "Env:      " } else { "          " };
+            let value = shell::quote(value);
+            writeln!(out, "   {} {}={}", label.green(), name, value)?;
+        }
+        writeln!(out, "   {} {}", "In:       ".green(), self.dir.display())?;
+        if let Some(shell) = &self.shell {
+            writeln!(
+                out,
+                "   {} {} (from {})",
+                "Shell:    ".green(),
+                shell.value,
+                shell.source
+            )?;
+        }
+        writeln!(out)?;
+        Ok(())
+    }
+}
+
+/// The steps of a chained command, one per line, numbered in run order
+pub fn print_steps(out: &mut impl Write, command: &str) -> io::Result<()> {
+    writeln!(out)?;
+    writeln!(out, "   {}", "Steps:".green())?;
+    for (i, step) in shell::split_steps(command).iter().enumerate() {
+        writeln!(out, "   {:>3}. {}", i + 1, step)?;
+    }
+    writeln!(out)?;
+    Ok(())
+}
+
+/// Ask whether to go ahead; only an explicit yes counts, so a closed or
+/// empty stdin declines
+fn confirm(input: &mut impl BufRead) -> Result<bool> {
+    eprint!("Run it? [y/N] ");
+    let mut answer = String::new();
+    input.read_line(&mut answer)?;
+    Ok(matches!(answer.trim().to_lowercase().as_str(), "y" | "yes"))
+}
+
+/// Stop unless the user says to go ahead with `script`. Without a terminal to
+/// ask on there's no one to say yes, so that's a refusal rather than a
+/// question; either way not running it is an error, so sl exits non-zero.
+fn confirm_run(script: &str, input: &mut impl BufRead, interactive: bool) -> Result<()> {
+    if !interactive {
+        bail!(
+            "refusing to run `{}` without --yes: stdin is not a terminal",
+            script
+        );
+    }
+    if !confirm(input)? {
+        bail!("Cancelled, `{}` was not run", script);
+    }
+    Ok(())
+}
+
+/// Run `script` through the project's package manager, forwarding the
+/// trailing arguments, and record it in the run history
+pub fn run_script(
+    cli: &Cli,
+    package_path: &Path,
+    package: &PackageJson,
+    script: &str,
+) -> Result<()> {
+    if !package.scripts.contains_key(script) {
+        bail!("No script named `{}` in {}", script, package_path.display());
+    }
+
+    let manager = PackageManager::detect(&crate::package_dir(package_path));
+    check_shell_support(manager, cli.shell.as_deref())?;
+    let mut invocation = Invocation::new(package_path, script, &cli.args, cli.shell.as_deref());
+    if let Some(dir) = &cli.cwd {
+        invocation.dir = dir.clone();
+    }
+    // Ahead of the shell setting, which --shell should decide; --env wins
+    // over the files
+    let mut vars = envs::merge_env_files(&cli.env_file)?;
+    vars.retain(|(name, _)| !cli.env.iter().any(|(n, _)| n == name));
+    vars.extend(cli.env.iter().cloned());
+    invocation.envs.splice(0..0, vars);
+    if cli.print_command {
+        return crate::write_stdout(format!("{}\n", invocation.shell_command()).as_bytes());
+    }
+    if cli.preview {
+        let mut out = Vec::new();
+        print_steps(&mut out, &package.scripts[script])?;
+        crate::write_stdout(&out)?;
+    }
+    let rules = config::Config::load(&crate::package_dir(package_path))?.destructive;
+    let destructive = destructive::destructive_match(script, &package.scripts[script], &rules);
+    if let Some(matched) = &destructive {
+        let warning = format!("⚠️  `{}` looks destructive: {}", script, matched);
+        eprintln!("{}", warning.yellow());
+    }
+    if (cli.preview || destructive.is_some()) && !cli.dry_run && !cli.yes {
+        let stdin = io::stdin();
+        let interactive = stdin.is_terminal();
+        confirm_run(script, &mut stdin.lock(), interactive)?;
+    }
+    if cli.dry_run {
+        let mut out = Vec::new();
+        invocation.print_dry_run(&mut out)?;
+        return crate::write_stdout(&out);
+    }
+
+    let dir = invocation.dir.clone();
+    let result = run_with_hooks(
+        &dir,
+        cli.pre_run.as_deref(),
+        cli.post_run.as_deref(),
+        || {
+            let mut command = Command::new(&invocation.program);
+            command
+                .current_dir(&invocation.dir)
+                .args(&invocation.args)
+                .envs(invocation.envs.iter().map(|(k, v)| (k, v)));
+            let status = match cli.timeout {
+                Some(seconds) => status_with_timeout(&mut command, Duration::from_secs(seconds)),
+                None => command.status().map(Some),
+            }
+            .with_context(|| format!("Failed to run {}", invocation.program))?;
+
+            // A failed history write shouldn't mask how the script itself went
+            if let Some(history_file) = history::history_file() {
+                let package = history::package_key(package_path);
+                if let Err(e) = history::record(&history_file, &package, script) {
+                    let warning = format!("⚠️  Could not record run history: {:#}", e);
+                    eprintln!("{}", warning.yellow());
+                }
+            }
+
+            let Some(status) = status else {
+                return Err(TimedOut {
+                    script: script.to_string(),
+                    seconds: cli.timeout.unwrap_or_default(),
+                }
+                .into());
+            };
+            if !status.success() {
+                return Err(ScriptFailed {
+                    script: script.to_string(),
+                    status,
+                }
+                .into());
+            }
+            Ok(())
+        },
+    );
+    if cli.notify {
+        notify_finished(script, &result);
+    }
+    result
+}
+
+/// `--shell` only reaches the script through `npm_config_script_shell`, which
+/// the other package managers don't read; rather than have it do nothing,
+/// refuse it for them
+fn check_shell_support(manager: PackageManager, shell: Option<&str>) -> Result<()> {
+    if shell.is_some() && manager != PackageManager::Npm {
+        bail!(
+            "--shell only works with npm; {} ignores npm's script-shell setting",
+            manager.name()
+        );
+    }
+    Ok(())
+}
+
+/// The title and text of the `--notify` notification for how `script` went
+fn notification(script: &str, result: &Result<()>) -> (String, String) {
+    match result {
+        Ok(()) => (format!("✅ {} succeeded", script), String::new()),
+        Err(e) => (format!("❌ {} failed", script), format!("{:#}", e)),
+    }
+}
+
+// Best effort: with no notification service to show it, there's no one to
+// tell either
+fn notify_finished(script: &str, result: &Result<()>) {
+    let (summary, body) = notification(script, result);
+    let _ = notify_rust::Notification::new()
+        .appname("sl")
+        .summary(&summary)
+        .body(&body)
+        .show();
+}
+
+/// Run `command` in a process group of its own, and kill the whole group
+/// (the package manager, the script and anything it started) if it's still
+/// going after `timeout`. `None` if it had to be killed.
+///
+/// The group gets the terminal while it runs, so Ctrl-C and reads from the
+/// tty reach the script as they would without `--timeout`; a SIGINT, SIGTERM
+/// or SIGHUP sent to sl itself is passed on to the group.
+fn status_with_timeout(command: &mut Command, timeout: Duration) -> io::Result<Option<ExitStatus>> {
+    #[cfg(unix)]
+    let _foreground = foreground::Group::prepare(command);
+    let mut child = command.spawn()?;
+    #[cfg(unix)]
+    let _forwarding = foreground::Forwarding::to(child.id());
+
+    // Polled rather than waited on from another thread, so that the group is
+    // only ever killed while its leader is unreaped and its id can't be reused
+    let deadline = Instant::now() + timeout;
+    loop {
+        if let Some(status) = child.try_wait()? {
+            return Ok(Some(status));
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            kill_tree(child.id());
+            child.wait()?;
+            return Ok(None);
+        }
+        thread::sleep((deadline - now).min(Duration::from_millis(50)));
+    }
+}
+
+#[cfg(unix)]
+mod foreground {
+    use std::os::unix::process::CommandExt;
+    use std::process::Command;
+    use std::sync::atomic::{AtomicI32, Ordering};
+
+    // The group signals are passed on to, 0 while there's none
+    static GROUP: AtomicI32 = AtomicI32::new(0);
+
+    const FORWARDED: [libc::c_int; 3] = [libc::SIGINT, libc::SIGTERM, libc::SIGHUP];
+
+    /// While alive, the terminal belongs to the group `command` starts, if sl
+    /// had it in the first place; dropping it takes the terminal back
+    pub struct Group {
+        tty: Option<libc::c_int>,
+    }
+
+    impl Group {
+        pub fn prepare(command: &mut Command) -> Group {
+            command.process_group(0);
+            // SAFETY: plain libc calls on our own stdin
+            let in_foreground = unsafe {
+                libc::isatty(libc::STDIN_FILENO) == 1
+                    && libc::tcgetpgrp(libc::STDIN_FILENO) == libc::getpgrp()
+            };
+            if !in_foreground {
+                return Group { tty: None };
+            }
+            // SAFETY: only async-signal-safe calls between fork and exec. The
+            // child takes the terminal itself so that it has it from its very
+            // first instruction; from a background group that needs SIGTTOU
+            // blocked.
+            unsafe {
+                command.pre_exec(|| {
+                    libc::setpgid(0, 0);
+                    with_sigttou_blocked(|| {
+                        libc::tcsetpgrp(libc::STDIN_FILENO, libc::getpgrp());
+                    });
+                    Ok(())
+                });
+            }
+            Group {
+                tty: Some(libc::STDIN_FILENO),
+            }
+        }
+    }
+
+    impl Drop for Group {
+        fn drop(&mut self) {
+            if let Some(tty) = self.tty {
+                // SAFETY: as above, now from what has become a background group
+                unsafe {
+                    with_sigttou_blocked(|| {
+                        libc::tcsetpgrp(tty, libc::getpgrp());
+                    });
+                }
+            }
+        }
+    }
+
+    unsafe fn with_sigttou_blocked(f: impl FnOnce()) {
+        let mut blocked: libc::sigset_t = std::mem::zeroed();
+        let mut previous: libc::sigset_t = std::mem::zeroed();
+        libc::sigemptyset(&mut blocked);
+        libc::sigaddset(&mut blocked, libc::SIGTTOU);
+        libc::sigprocmask(libc::SIG_BLOCK, &blocked, &mut previous);
+        f();
+        libc::sigprocmask(libc::SIG_SETMASK, &previous, std::ptr::null_mut());
+    }
+
+    /// While alive, SIGINT, SIGTERM and SIGHUP sent to sl go to the group
+    /// `pid` leads instead, which stops with them or not as it sees fit
+    pub struct Forwarding {
+        previous: Vec<(libc::c_int, libc::sighandler_t)>,
+    }
+
+    impl Forwarding {
+        pub fn to(pid: u32) -> Forwarding {
+            GROUP.store(pid as i32, Ordering::SeqCst);
+            let handler = forward as extern "C" fn(libc::c_int) as libc::sighandler_t;
+            let previous = FORWARDED
+                .iter()
+                // SAFETY: `forward` only makes async-signal-safe calls
+                .map(|&signal| (signal, unsafe { libc::signal(signal, handler) }))
+                .collect();
+            Forwarding { previous }
+        }
+    }
+
+    impl Drop for Forwarding {
+        fn drop(&mut self) {
+            for &(signal, handler) in &self.previous {
+                // SAFETY: puts back what was there before
+                unsafe {
+                    libc::signal(signal, handler);
+                }
+            }
+            GROUP.store(0, Ordering::SeqCst);
+        }
+    }
+
+    extern "C" fn forward(signal: libc::c_int) {
+        let group = GROUP.load(Ordering::SeqCst);
+        if group > 0 {
+            // SAFETY: kill is async-signal-safe
+            unsafe {
+                libc::kill(-group, signal);
+            }
+        }
+    }
+}
+
+#[cfg(unix)]
+fn kill_tree(pid: u32) {
+    // A negative pid signals every process in the group `pid` leads
+    unsafe {
+        libc::kill(-(pid as libc::pid_t), libc::SIGKILL);
+    }
+}
+
+#[cfg(windows)]
+fn kill_tree(pid: u32) {
+    let _ = Command::new("taskkill")
+        .args(["/F", "/T", "/PID", &pid.to_string()])
+        .output();
+}
+
+/// Call `script` between the `--pre-run` and `--post-run` commands. A failing
+/// pre-run command stops the script from running; the post-run one runs
+/// either way, and the script's own error wins over its.
+fn run_with_hooks(
+    dir: &Path,
+    pre_run: Option<&str>,
+    post_run: Option<&str>,
+    script: impl FnOnce() -> Result<()>,
+) -> Result<()> {
+    if let Some(command) = pre_run {
+        run_hook(dir, "--pre-run", command)?;
+    }
+    let result = script();
+    match post_run {
+        Some(command) => result.and(run_hook(dir, "--post-run", command)),
+        None => result,
+    }
+}
+
+// Each hook gets a shell of its own, so it can't change the script's
+// environment
+fn run_hook(dir: &Path, flag: &str, command: &str) -> Result<()> {
+    let status = Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .current_dir(dir)
+        .status()
+        .with_context(|| format!("Failed to start the {} command", flag))?;
+    if !status.success() {
+        bail!("{} command `{}` failed ({})", flag, command, status);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_print_steps() {
+        control::set_override(false);
+        let mut out = Vec::new();
+        print_steps(&mut out, "tsc && jest || echo 'tests; failed'").unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   Steps:\n     1. tsc\n     2. && jest\n     3. || echo 'tests; failed'\n\n"
+        );
+    }
+
+    #[test]
+    fn test_shell_overrides_npmrc() {
+        control::set_override(false);
+        let dir = tempfile::tempdir().unwrap();
+        std::fs::write(dir.path().join(".npmrc"), "script-shell=/bin/zsh\n").unwrap();
+        let package_path = dir.path().join("package.json");
+
+        let configured = Invocation::new(&package_path, "build", &[], None);
+        assert_eq!(configured.shell.unwrap().value, "/bin/zsh");
+        assert_eq!(configured.dir, dir.path());
+
+        let invocation = Invocation::new(&package_path, "build", &[], Some("bash"));
+        assert_eq!(
+            invocation.envs,
+            [("npm_config_script_shell".to_string(), "bash".to_string())]
+        );
+        let mut out = Vec::new();
+        invocation.print_dry_run(&mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("   Shell:     bash (from --shell)\n"));
+    }
+
+    #[test]
+    fn test_run_with_hooks() {
+        let dir = tempfile::tempdir().unwrap();
+        let sentinel = |name: &str| dir.path().join(name);
+        let touch = |name: &str| format!("touch '{}'", sentinel(name).display());
+
+        let ran = std::cell::Cell::new(false);
+        run_with_hooks(
+            dir.path(),
+            Some(&touch("pre")),
+            Some(&touch("post")),
+            || {
+                assert!(sentinel("pre").exists());
+                assert!(!sentinel("post").exists());
+                ran.set(true);
+                Ok(())
+            },
+        )
+        .unwrap();
+        assert!(ran.get() && sentinel("post").exists());
+
+        // A failing pre-run command means no script and no post-run
+        let err = run_with_hooks(dir.path(), Some("exit 3"), Some(&touch("post2")), || {
+            panic!("script should not run")
+        })
+        .unwrap_err();
+        assert!(err
+            .to_string()
+            .contains("--pre-run command `exit 3` failed"));
+        assert!(!sentinel("post2").exists());
+
+        // The post-run command runs after a failed script too
+        let err = run_with_hooks(dir.path(), None, Some(&touch("post3")), || {
+            bail!("Script `test` failed")
+        })
+        .unwrap_err();
+        assert_eq!(err.to_string(), "Script `test` failed");
+        assert!(sentinel("post3").exists());
+
+        let err = run_with_hooks(dir.path(), None, Some("false"), || Ok(())).unwrap_err();
+        assert!(err
+            .to_string()
+            .starts_with("--post-run command `false` failed"));
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_status_with_timeout_kills_the_group() {
+        let dir = tempfile::tempdir().unwrap();
+        let late = dir.path().join("late");
+        let mut command = Command::new("sh");
+        command.arg("-c").arg(format!(
+            "(sleep 0.5; touch '{}') & sleep 30",
+            late.display()
+        ));
+
+        let started = std::time::Instant::now();
+        let status = status_with_timeout(&mut command, Duration::from_millis(100)).unwrap();
+        assert!(status.is_none());
+        assert!(started.elapsed() < Duration::from_secs(10));
+        // The background job went down with the rest of the group
+        thread::sleep(Duration::from_secs(1));
+        assert!(!late.exists());
+
+        let mut command = Command::new("sh");
+        command.args(["-c", "exit 3"]);
+        let status = status_with_timeout(&mut command, Duration::from_secs(30)).unwrap();
+        assert_eq!(status.unwrap().code(), Some(3));
+    }
+
+    #[test]
+    fn test_exit_code_mirrors_the_script() {
+        let failed = |command| {
+            let status = Command::new("sh").args(["-c", command]).status().unwrap();
+            anyhow::Error::from(ScriptFailed {
+                script: "test".to_string(),
+                status,
+            })
+        };
+
+        let error = failed("exit 3");
+        assert_eq!(exit_code(&error), Some(3));
+        assert_eq!(error.to_string(), "Script `test` failed (exit status: 3)");
+        #[cfg(unix)]
+        assert_eq!(
+            exit_code(&failed("kill -TERM $$")),
+            Some(128 + libc::SIGTERM)
+        );
+
+        let timed_out = anyhow::Error::from(TimedOut {
+            script: "test".to_string(),
+            seconds: 5,
+        });
+        assert_eq!(exit_code(&timed_out), Some(TIMEOUT_EXIT_CODE));
+        assert_eq!(exit_code(&anyhow::anyhow!("No script named `x`")), None);
+    }
+
+    #[test]
+    fn test_check_shell_support() {
+        assert!(check_shell_support(PackageManager::Npm, Some("bash")).is_ok());
+        assert!(check_shell_support(PackageManager::Pnpm, None).is_ok());
+        assert_eq!(
+            check_shell_support(PackageManager::Bun, Some("bash"))
+                .unwrap_err()
+                .to_string(),
+            "--shell only works with npm; bun ignores npm's script-shell setting"
+        );
+    }
+
+    #[test]
+    fn test_notification() {
+        assert_eq!(
+            notification("build", &Ok(())),
+            ("✅ build succeeded".to_string(), String::new())
+        );
+        let timed_out = anyhow::Error::from(TimedOut {
+            script: "build".to_string(),
+            seconds: 5,
+        });
+        let body = timed_out.to_string();
+        let (summary, shown) = notification("build", &Err(timed_out));
+        assert_eq!(summary, "❌ build failed");
+        assert_eq!(shown, body);
+    }
+
+    #[test]
+    fn test_shell_command() {
+        let mut invocation = Invocation {
+            program: "npm".to_string(),
+            args: ["run", "build", "--", "--out dir"]
+                .map(String::from)
+                .to_vec(),
+            envs: vec![("NODE_ENV".to_string(), "production".to_string())],
+            shell: None,
+            dir: PathBuf::from("."),
+        };
+        assert_eq!(
+            invocation.shell_command(),
+            "NODE_ENV=production npm run build -- '--out dir'"
+        );
+
+        invocation.envs.clear();
+        invocation.dir = PathBuf::from("packages/my app");
+        assert_eq!(
+            invocation.shell_command(),
+            "cd 'packages/my app' && npm run build -- '--out dir'"
+        );
+
+        // It really runs
+        invocation.program = "echo".to_string();
+        invocation.envs = vec![("GREETING".to_string(), "hi there".to_string())];
+        invocation.dir = PathBuf::from(".");
+        invocation.args = vec!["\"$GREETING\"".to_string()];
+        let output = Command::new("sh")
+            .args(["-c", &invocation.shell_command()])
+            .output()
+            .unwrap();
+        assert_eq!(String::from_utf8(output.stdout).unwrap(), "\"$GREETING\"\n");
+    }
+
+    #[test]
+    fn test_dry_run_shows_env() {
+        control::set_override(false);
+        let invocation = Invocation {
+            program: "npm".to_string(),
+            args: vec!["run".to_string(), "build".to_string()],
+            envs: vec![
+                ("NODE_ENV".to_string(), "production".to_string()),
+                ("GREETING".to_string(), "hi there".to_string()),
+                ("npm_config_script_shell".to_string(), "bash".to_string()),
+            ],
+            shell: Some(npmrc::Setting {
+                value: "bash".to_string(),
+                source: "--shell".to_string(),
+            }),
+            dir: PathBuf::from("packages/app"),
+        };
+        let mut out = Vec::new();
+        invocation.print_dry_run(&mut out).unwrap();
+        assert_eq!(
+            String::from_utf8(out).unwrap(),
+            "\n   Would run: npm run build\n\
+             \x20  Env:       NODE_ENV=production\n\
+             \x20             GREETING='hi there'\n\
+             \x20  In:        packages/app\n\
+             \x20  Shell:     bash (from --shell)\n\n"
+        );
+    }
+
+    #[test]
+    fn test_confirm() {
+        assert!(confirm(&mut "y\n".as_bytes()).unwrap());
+        assert!(confirm(&mut "Yes\n".as_bytes()).unwrap());
+        assert!(!confirm(&mut "\n".as_bytes()).unwrap());
+        assert!(!confirm(&mut "".as_bytes()).unwrap());
+        assert!(!confirm(&mut "nope\n".as_bytes()).unwrap());
+    }
+
+    #[test]
+    fn test_confirm_run() {
+        assert!(confirm_run("clean", &mut "y\n".as_bytes(), true).is_ok());
+        assert_eq!(
+            confirm_run("clean", &mut "".as_bytes(), true)
+                .unwrap_err()
+                .to_string(),
+            "Cancelled, `clean` was not run"
+        );
+        assert!(confirm_run("clean", &mut "n\n".as_bytes(), true).is_err());
+        // Not even read without a terminal
+        assert_eq!(
+            confirm_run("clean", &mut "y\n".as_bytes(), false)
+                .unwrap_err()
+                .to_string(),
+            "refusing to run `clean` without --yes: stdin is not a terminal"
+        );
+    }
+}
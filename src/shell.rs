@@ -0,0 +1,557 @@
+use regex::{Captures, Regex};
+use std::collections::HashMap;
+use std::sync::OnceLock;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Token {
+    /// A word with its quotes and escapes removed
+    Word(String),
+    /// `&&`, `||`, `|`, `;`, `&`, `(` or `)`
+    Operator(String),
+}
+
+const OPERATORS: &[&str] = &["&&", "||", "|", ";", "&", "(", ")"];
+
+/// Split a script command into words and control operators.
+///
+/// This is just enough shell to reason about package.json scripts, not a
+/// POSIX parser: quotes, backslash escapes and the operators that chain
+/// commands are understood; expansions and redirections are left as words.
+pub fn tokenize(command: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut in_word = false;
+    let mut chars = command.chars().peekable();
+
+    while let Some(c) = chars.next() {
+        match c {
+            '\'' => {
+                in_word = true;
+                for c in chars.by_ref() {
+                    if c == '\'' {
+                        break;
+                    }
+                    word.push(c);
+                }
+            }
+            '"' => {
+                in_word = true;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '"' => break,
+                        '\\' if matches!(chars.peek(), Some('"' | '\\' | '$' | '`')) => {
+                            word.extend(chars.next());
+                        }
+                        _ => word.push(c),
+                    }
+                }
+            }
+            '\\' => {
+                in_word = true;
+                word.extend(chars.next());
+            }
+            c if c.is_whitespace() => {
+                if in_word {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                    in_word = false;
+                }
+            }
+            c if "&|;()".contains(c) => {
+                if in_word {
+                    tokens.push(Token::Word(std::mem::take(&mut word)));
+                    in_word = false;
+                }
+                let mut op = c.to_string();
+                if let Some(&next) = chars.peek() {
+                    let doubled = format!("{}{}", c, next);
+                    if OPERATORS.contains(&doubled.as_str()) {
+                        op = doubled;
+                        chars.next();
+                    }
+                }
+                tokens.push(Token::Operator(op));
+            }
+            _ => {
+                in_word = true;
+                word.push(c);
+            }
+        }
+    }
+
+    if in_word {
+        tokens.push(Token::Word(word));
+    }
+    tokens
+}
+
+/// The words of each simple command in `command`, split on control operators
+pub fn simple_commands(command: &str) -> Vec<Vec<String>> {
+    let mut commands = vec![Vec::new()];
+    for token in tokenize(command) {
+        match token {
+            Token::Word(word) => commands.last_mut().unwrap().push(word),
+            Token::Operator(_) => commands.push(Vec::new()),
+        }
+    }
+    commands.retain(|words| !words.is_empty());
+    commands
+}
+
+/// Whether `word` is a `NAME=value` environment assignment
+pub fn is_assignment(word: &str) -> bool {
+    match word.split_once('=') {
+        Some((name, _)) => {
+            let mut chars = name.chars();
+            chars
+                .next()
+                .is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+                && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+        }
+        None => false,
+    }
+}
+
+/// The program each simple command runs, skipping leading assignments
+pub fn programs(command: &str) -> Vec<String> {
+    simple_commands(command)
+        .into_iter()
+        .filter_map(|words| words.into_iter().find(|word| !is_assignment(word)))
+        .collect()
+}
+
+/// Split leading `NAME=value` assignments off `command`, returning them and
+/// the rest of the command. Values and the rest are kept as written, quotes
+/// included, so they can be pasted back into a shell.
+pub fn split_env_prefix(command: &str) -> (Vec<(String, String)>, String) {
+    let mut env = Vec::new();
+    let mut rest = command.trim_start();
+    loop {
+        let end = word_end(rest);
+        let word = &rest[..end];
+        if !is_assignment(word) || tokenize(word).len() != 1 {
+            break;
+        }
+        let (name, value) = word.split_once('=').unwrap();
+        env.push((name.to_string(), value.to_string()));
+        rest = rest[end..].trim_start();
+    }
+    (env, rest.to_string())
+}
+
+// Byte offset where the first word of `s` ends, skipping over quoted and
+// escaped whitespace
+fn word_end(s: &str) -> usize {
+    let mut quote = None;
+    let mut chars = s.char_indices();
+    while let Some((i, c)) = chars.next() {
+        match (quote, c) {
+            (None, '\\') | (Some('"'), '\\') => {
+                chars.next();
+            }
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, c) if c.is_whitespace() || "&|;()".contains(c) => return i,
+            _ => {}
+        }
+    }
+    s.len()
+}
+
+/// Split the `npm run ` (or `yarn `, `pnpm run `, `bun run `...) a wrapper
+/// script starts with off the rest of `command`; the prefix is empty when
+/// there's none
+pub fn split_run_prefix(command: &str) -> (&str, &str) {
+    static PREFIX: OnceLock<Regex> = OnceLock::new();
+    let prefix = PREFIX.get_or_init(|| {
+        Regex::new(r"^(npm run(-script)?|yarn( run)?|pnpm( run)?|bun run)\s+\S").unwrap()
+    });
+    match prefix.find(command) {
+        // Up to the first character of the rest
+        Some(found) => command.split_at(found.end() - 1),
+        None => ("", command),
+    }
+}
+
+/// Split `command` into the steps chained with `&&`, `||` or `;`, each step
+/// but the first starting with the operator that joins it on. Pipelines stay
+/// one step, and the text is kept as written.
+pub fn split_steps(command: &str) -> Vec<String> {
+    let mut steps = Vec::new();
+    let mut step = String::new();
+    let mut joined_by = "";
+    let mut quote = None;
+    let mut chars = command.chars().peekable();
+    while let Some(c) = chars.next() {
+        let operator = match (quote, c) {
+            (None, '&') if chars.peek() == Some(&'&') => Some("&&"),
+            (None, '|') if chars.peek() == Some(&'|') => Some("||"),
+            (None, ';') => Some(";"),
+            _ => None,
+        };
+        if let Some(operator) = operator {
+            if operator.len() == 2 {
+                chars.next();
+            }
+            push_step(&mut steps, joined_by, &step);
+            step.clear();
+            joined_by = operator;
+            continue;
+        }
+
+        step.push(c);
+        match (quote, c) {
+            (None | Some('"'), '\\') => step.extend(chars.next()),
+            (None, '\'' | '"') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            _ => {}
+        }
+    }
+    push_step(&mut steps, joined_by, &step);
+    steps
+}
+
+fn push_step(steps: &mut Vec<String>, joined_by: &str, step: &str) {
+    let step = step.trim();
+    match (joined_by, step) {
+        (_, "") => {}
+        ("", step) => steps.push(step.to_string()),
+        (operator, step) => steps.push(format!("{} {}", operator, step)),
+    }
+}
+
+/// Substitute `$NAME` and `${NAME}` from `vars`, as the shell would outside
+/// single quotes. Variables not in `vars` are left as written.
+pub fn expand_env_vars(command: &str, vars: &HashMap<String, String>) -> String {
+    let mut out = String::new();
+    let mut in_single_quotes = false;
+    // A `'` between double quotes is just a character
+    let mut in_double_quotes = false;
+    let mut rest = command;
+    while let Some(c) = rest.chars().next() {
+        rest = &rest[c.len_utf8()..];
+        match c {
+            '\'' if !in_double_quotes => in_single_quotes = !in_single_quotes,
+            '"' if !in_single_quotes => in_double_quotes = !in_double_quotes,
+            '\\' if !in_single_quotes => {
+                out.push(c);
+                if let Some(next) = rest.chars().next() {
+                    out.push(next);
+                    rest = &rest[next.len_utf8()..];
+                }
+                continue;
+            }
+            '$' if !in_single_quotes => {
+                let (name, len) = match rest.strip_prefix('{') {
+                    Some(braced) => match braced.find('}') {
+                        Some(end) => (&braced[..end], end + 2),
+                        None => ("", 0),
+                    },
+                    None => {
+                        let end = rest
+                            .find(|c: char| !(c.is_ascii_alphanumeric() || c == '_'))
+                            .unwrap_or(rest.len());
+                        (&rest[..end], end)
+                    }
+                };
+                if let Some(value) = vars.get(name).filter(|_| is_var_name(name)) {
+                    out.push_str(value);
+                    rest = &rest[len..];
+                    continue;
+                }
+            }
+            _ => {}
+        }
+        out.push(c);
+    }
+    out
+}
+
+fn is_var_name(name: &str) -> bool {
+    is_assignment(&format!("{}=", name))
+}
+
+/// Whether `command` looks like it expects arguments: it reads positional
+/// parameters (`$1`, `$@`, `${2:-x}`) or ends in a bare `--` waiting for
+/// forwarded ones
+pub fn expects_args(command: &str) -> bool {
+    let positional = ["$@", "$*", "${@", "${*"]
+        .iter()
+        .any(|p| command.contains(p))
+        || command.match_indices('$').any(|(i, _)| {
+            let rest = command[i + 1..].trim_start_matches('{');
+            rest.starts_with(|c: char| c.is_ascii_digit() && c != '0')
+        });
+    positional || command.trim_end().ends_with(" --")
+}
+
+// Reserved words after which another command follows
+const LEADING_KEYWORDS: &[&str] = &["if", "then", "else", "elif", "do", "while", "until", "!"];
+const OTHER_KEYWORDS: &[&str] = &["fi", "for", "done", "case", "esac"];
+
+/// `command` with ANSI colors for a terminal: keywords cyan, quoted strings
+/// green, control operators yellow, `$VAR`s magenta and the program each
+/// command runs bold white. Like `tokenize`, a close reading rather than a
+/// full parse.
+pub fn highlight_shell(command: &str) -> String {
+    static TOKEN: OnceLock<Regex> = OnceLock::new();
+    let token = TOKEN.get_or_init(|| {
+        Regex::new(r#"'[^']*'?|"(?:[^"\\]|\\.)*"?|&&|\|\||[|;&()]|\$\{?\w+\}?|[^\s'"|;&()$]+"#)
+            .unwrap()
+    });
+
+    #[derive(Clone, Copy, PartialEq)]
+    enum Next {
+        Command,
+        Argument,
+        ForVariable,
+        In,
+    }
+
+    let paint = |code: &str, text: &str| format!("\x1b[{}m{}\x1b[0m", code, text);
+    let mut next = Next::Command;
+    token
+        .replace_all(command, |caps: &Captures| {
+            let text = &caps[0];
+            let expected = std::mem::replace(&mut next, Next::Argument);
+            if OPERATORS.contains(&text) {
+                next = Next::Command;
+                paint("33", text)
+            } else if text.starts_with(['\'', '"']) {
+                paint("32", text)
+            } else if text.starts_with('$') {
+                paint("35", text)
+            } else if expected == Next::Command && LEADING_KEYWORDS.contains(&text) {
+                next = Next::Command;
+                paint("36", text)
+            } else if expected == Next::Command && OTHER_KEYWORDS.contains(&text) {
+                if text == "for" {
+                    next = Next::ForVariable;
+                }
+                paint("36", text)
+            } else if expected == Next::ForVariable {
+                next = Next::In;
+                text.to_string()
+            } else if expected == Next::In && text == "in" {
+                paint("36", text)
+            } else if expected == Next::Command && is_assignment(text) {
+                next = Next::Command;
+                text.to_string()
+            } else if expected == Next::Command {
+                paint("1;37", text)
+            } else {
+                text.to_string()
+            }
+        })
+        .into_owned()
+}
+
+/// Quote `word` so a POSIX shell reads it back unchanged
+pub fn quote(word: &str) -> String {
+    let safe = |c: char| c.is_ascii_alphanumeric() || "-_./:=@%+,".contains(c);
+    if !word.is_empty() && word.chars().all(safe) {
+        return word.to_string();
+    }
+    format!("'{}'", word.replace('\'', r"'\''"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn word(s: &str) -> Token {
+        Token::Word(s.to_string())
+    }
+
+    fn op(s: &str) -> Token {
+        Token::Operator(s.to_string())
+    }
+
+    #[test]
+    fn test_highlight_shell() {
+        let cyan = |s: &str| format!("\x1b[36m{}\x1b[0m", s);
+        let green = |s: &str| format!("\x1b[32m{}\x1b[0m", s);
+        let yellow = |s: &str| format!("\x1b[33m{}\x1b[0m", s);
+        let magenta = |s: &str| format!("\x1b[35m{}\x1b[0m", s);
+        let bold = |s: &str| format!("\x1b[1;37m{}\x1b[0m", s);
+
+        assert_eq!(
+            highlight_shell(r#"if [ -n "$CI" ]; then NODE_ENV=test jest --ci; fi"#),
+            format!(
+                "{} {} -n {} ]{} {} NODE_ENV=test {} --ci{} {}",
+                cyan("if"),
+                bold("["),
+                green(r#""$CI""#),
+                yellow(";"),
+                cyan("then"),
+                bold("jest"),
+                yellow(";"),
+                cyan("fi")
+            )
+        );
+        assert_eq!(
+            highlight_shell("for f in src/*.js; do echo $f | wc -l; done"),
+            format!(
+                "{} f {} src/*.js{} {} {} {} {} {} -l{} {}",
+                cyan("for"),
+                cyan("in"),
+                yellow(";"),
+                cyan("do"),
+                bold("echo"),
+                magenta("$f"),
+                yellow("|"),
+                bold("wc"),
+                yellow(";"),
+                cyan("done")
+            )
+        );
+        // Keywords only count where a command would start
+        assert_eq!(
+            highlight_shell("echo if 'done' && grep in"),
+            format!(
+                "{} if {} {} {} in",
+                bold("echo"),
+                green("'done'"),
+                yellow("&&"),
+                bold("grep")
+            )
+        );
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            tokenize(r#"echo 'a && b' "c \"d\"" e\ f&&ls|wc -l; (x)"#),
+            [
+                word("echo"),
+                word("a && b"),
+                word(r#"c "d""#),
+                word("e f"),
+                op("&&"),
+                word("ls"),
+                op("|"),
+                word("wc"),
+                word("-l"),
+                op(";"),
+                op("("),
+                word("x"),
+                op(")"),
+            ]
+        );
+        assert_eq!(tokenize("echo ''"), [word("echo"), word("")]);
+    }
+
+    #[test]
+    fn test_programs() {
+        assert_eq!(
+            programs("NODE_ENV=production webpack && ./deploy.sh || echo failed"),
+            ["webpack", "./deploy.sh", "echo"]
+        );
+        assert!(programs("").is_empty());
+    }
+
+    #[test]
+    fn test_split_run_prefix() {
+        assert_eq!(
+            split_run_prefix("npm run build -- --watch"),
+            ("npm run ", "build -- --watch")
+        );
+        assert_eq!(split_run_prefix("yarn  lint"), ("yarn  ", "lint"));
+        assert_eq!(
+            split_run_prefix("pnpm run test:unit"),
+            ("pnpm run ", "test:unit")
+        );
+        assert_eq!(split_run_prefix("npm run"), ("", "npm run"));
+        assert_eq!(split_run_prefix("npx tsc"), ("", "npx tsc"));
+        assert_eq!(split_run_prefix("yarnpkg build"), ("", "yarnpkg build"));
+    }
+
+    #[test]
+    fn test_quote() {
+        assert_eq!(quote("build:prod"), "build:prod");
+        assert_eq!(quote("--port=3000"), "--port=3000");
+        assert_eq!(quote("two words"), "'two words'");
+        assert_eq!(quote("it's"), r"'it'\''s'");
+        assert_eq!(quote(""), "''");
+        assert_eq!(tokenize(&quote("it's $HOME")), [word("it's $HOME")]);
+    }
+
+    #[test]
+    fn test_expects_args() {
+        assert!(expects_args("./deploy.sh $1"));
+        assert!(expects_args("node cli.js \"$@\""));
+        assert!(expects_args("echo ${2:-default}"));
+        assert!(expects_args("jest --"));
+        assert!(!expects_args("tsc && echo $0 $HOME"));
+        assert!(!expects_args("jest -- --coverage"));
+    }
+
+    fn env(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(name, value)| (name.to_string(), value.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_split_env_prefix() {
+        assert_eq!(
+            split_env_prefix("webpack --mode production"),
+            (env(&[]), "webpack --mode production".to_string())
+        );
+        assert_eq!(
+            split_env_prefix("NODE_ENV=development vite"),
+            (env(&[("NODE_ENV", "development")]), "vite".to_string())
+        );
+        assert_eq!(
+            split_env_prefix(r#"CI=1  DEBUG="app:* db"  jest --runInBand && X=1"#),
+            (
+                env(&[("CI", "1"), ("DEBUG", r#""app:* db""#)]),
+                "jest --runInBand && X=1".to_string()
+            )
+        );
+        assert_eq!(
+            split_env_prefix("FOO=bar"),
+            (env(&[("FOO", "bar")]), String::new())
+        );
+    }
+
+    #[test]
+    fn test_split_steps() {
+        assert_eq!(
+            split_steps(
+                r#"tsc -p . && echo "a && b" | tee log||echo 'failed; really' ; rm -rf tmp\;x"#
+            ),
+            [
+                "tsc -p .",
+                r#"&& echo "a && b" | tee log"#,
+                "|| echo 'failed; really'",
+                r"; rm -rf tmp\;x",
+            ]
+        );
+        assert_eq!(split_steps("jest"), ["jest"]);
+        assert_eq!(split_steps("lint;"), ["lint"]);
+    }
+
+    #[test]
+    fn test_expand_env_vars() {
+        let vars = HashMap::from([
+            ("NODE_ENV".to_string(), "production".to_string()),
+            ("PORT".to_string(), "8080".to_string()),
+        ]);
+        assert_eq!(
+            expand_env_vars("node server.js --env $NODE_ENV --port=${PORT}", &vars),
+            "node server.js --env production --port=8080"
+        );
+        assert_eq!(
+            expand_env_vars(
+                r#"echo '$NODE_ENV' "$NODE_ENV" \$PORT $HOME $1 ${PORT"#,
+                &vars
+            ),
+            r#"echo '$NODE_ENV' "production" \$PORT $HOME $1 ${PORT"#
+        );
+        assert_eq!(
+            expand_env_vars(r#"echo "it's $NODE_ENV" '$PORT' "\"$PORT\"""#, &vars),
+            r#"echo "it's production" '$PORT' "\"8080\"""#
+        );
+    }
+}
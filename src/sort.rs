@@ -0,0 +1,172 @@
+use anyhow::{Context, Result};
+use serde::de::{Deserializer, IgnoredAny, MapAccess, Visitor};
+use serde::Deserialize;
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum SortBy {
+    /// Alphabetically by script name
+    Name,
+    /// In the order the scripts are written in package.json
+    Source,
+    /// Alphabetically by command, so scripts running the same tool group up
+    Command,
+    /// By command length, shortest first
+    Length,
+    /// The scripts named in `order` of .scriptrc.json first, in that order,
+    /// then the rest by name
+    Priority,
+    /// Not at all: the scripts stay in the order they were read in
+    None,
+}
+
+/// Sort by `sort_by`, then by name wherever that leaves a tie (equal
+/// commands, or scripts missing from `order`), so the order never depends on
+/// how the scripts were read in. `order` is the written order for
+/// `SortBy::Source` and the configured one for `SortBy::Priority`, where
+/// names without a script are simply skipped. `SortBy::None` leaves them be.
+pub fn sort_scripts(scripts: &mut [(String, String)], sort_by: SortBy, order: &[String]) {
+    if sort_by == SortBy::None {
+        return;
+    }
+    let position = |name: &String| order.iter().position(|n| n == name).unwrap_or(usize::MAX);
+    scripts.sort_by(|(a_name, a_command), (b_name, b_command)| {
+        let primary = match sort_by {
+            SortBy::Name => std::cmp::Ordering::Equal,
+            SortBy::Source | SortBy::Priority => position(a_name).cmp(&position(b_name)),
+            SortBy::Command => a_command.cmp(b_command),
+            SortBy::Length => a_command.chars().count().cmp(&b_command.chars().count()),
+            SortBy::None => unreachable!(),
+        };
+        primary.then_with(|| a_name.cmp(b_name))
+    });
+}
+
+/// Script names in the order they're written in the package.json at `path`.
+///
+/// This is a second, raw pass over the file, so that it's the order written
+/// there even after `--merge` has laid other scripts over the map that
+/// `PackageJson` is deserialized into.
+pub fn read_source_order(path: &Path) -> Result<Vec<String>> {
+    let content =
+        fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()))?;
+    source_order(&content).with_context(|| format!("Failed to parse {} as JSON", path.display()))
+}
+
+fn source_order(content: &str) -> Result<Vec<String>> {
+    #[derive(Deserialize)]
+    struct ScriptKeys {
+        #[serde(default, deserialize_with = "keys_in_order")]
+        scripts: Vec<String>,
+    }
+
+    let keys: ScriptKeys = serde_json::from_str(content)?;
+    Ok(keys.scripts)
+}
+
+fn keys_in_order<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<String>, D::Error> {
+    struct KeysVisitor;
+
+    impl<'de> Visitor<'de> for KeysVisitor {
+        type Value = Vec<String>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            f.write_str("a map of scripts")
+        }
+
+        fn visit_map<A: MapAccess<'de>>(self, mut map: A) -> Result<Vec<String>, A::Error> {
+            let mut keys = Vec::new();
+            while let Some((key, IgnoredAny)) = map.next_entry::<String, IgnoredAny>()? {
+                // A duplicated key keeps the position it was first written at
+                if !keys.contains(&key) {
+                    keys.push(key);
+                }
+            }
+            Ok(keys)
+        }
+    }
+
+    deserializer.deserialize_map(KeysVisitor)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_sort_by_source() {
+        let content = r#"{
+            "name": "app",
+            "scripts": {
+                "start": "node index.js",
+                "build": "tsc",
+                "test": "jest",
+                "build": "tsc -p ."
+            }
+        }"#;
+        let order = source_order(content).unwrap();
+        assert_eq!(order, ["start", "build", "test"]);
+
+        let mut scripts: Vec<_> = ["build", "start", "test"]
+            .iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect();
+        sort_scripts(&mut scripts, SortBy::Source, &order);
+
+        let names: Vec<_> = scripts.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["start", "build", "test"]);
+    }
+
+    #[test]
+    fn test_ties_break_by_name() {
+        let scripts: Vec<_> = [
+            ("test", "jest"),
+            ("check", "tsc"),
+            ("ci", "jest"),
+            ("build", "tsc"),
+        ]
+        .iter()
+        .map(|(name, command)| (name.to_string(), command.to_string()))
+        .collect();
+        let sorted = |sort_by, order: &[String]| {
+            let mut names = Vec::new();
+            // Whichever order they come in, they come out the same
+            for rotation in 0..scripts.len() {
+                let mut scripts = scripts.clone();
+                scripts.rotate_left(rotation);
+                sort_scripts(&mut scripts, sort_by, order);
+                names.push(
+                    scripts
+                        .into_iter()
+                        .map(|(name, _)| name)
+                        .collect::<Vec<_>>(),
+                );
+            }
+            names.dedup();
+            assert_eq!(names.len(), 1);
+            names.remove(0)
+        };
+
+        assert_eq!(
+            sorted(SortBy::Command, &[]),
+            ["ci", "test", "build", "check"]
+        );
+        assert_eq!(
+            sorted(SortBy::Source, &["test".to_string()]),
+            ["test", "build", "check", "ci"]
+        );
+        assert_eq!(sorted(SortBy::Name, &[]), ["build", "check", "ci", "test"]);
+        assert_eq!(
+            sorted(SortBy::Length, &[]),
+            ["build", "check", "ci", "test"]
+        );
+        // Configured names without a script are skipped over
+        let priority = ["dev", "test", "ci"].map(String::from);
+        assert_eq!(
+            sorted(SortBy::Priority, &priority),
+            ["test", "ci", "build", "check"]
+        );
+    }
+}
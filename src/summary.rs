@@ -0,0 +1,125 @@
+use regex::Regex;
+use serde::Deserialize;
+
+use crate::shell;
+
+/// A command pattern and what commands matching it do
+#[derive(Debug, Clone, Deserialize)]
+#[serde(try_from = "RawRule")]
+pub struct SummaryRule {
+    pattern: Regex,
+    summary: String,
+}
+
+#[derive(Deserialize)]
+#[serde(deny_unknown_fields)]
+struct RawRule {
+    pattern: String,
+    summary: String,
+}
+
+impl TryFrom<RawRule> for SummaryRule {
+    type Error = regex::Error;
+
+    fn try_from(raw: RawRule) -> Result<Self, Self::Error> {
+        Ok(SummaryRule {
+            pattern: Regex::new(&raw.pattern)?,
+            summary: raw.summary,
+        })
+    }
+}
+
+// Checked in order, so more specific patterns come first
+const BUILT_IN: &[(&str, &str)] = &[
+    (r"^jest\b", "Run Jest tests"),
+    (r"^vitest\b", "Run Vitest tests"),
+    (r"^mocha\b", "Run Mocha tests"),
+    (r"^(cypress|playwright) ", "Run end-to-end tests"),
+    (r"^eslint\b.*--fix", "Lint and fix with ESLint"),
+    (r"^eslint\b", "Lint with ESLint"),
+    (r"^prettier\b.*--(write|w)\b", "Format with Prettier"),
+    (r"^prettier\b", "Check formatting with Prettier"),
+    (r"^tsc\b.*--noEmit", "Type-check with TypeScript"),
+    (r"^tsc\b", "Compile TypeScript"),
+    (
+        r"^webpack\b.*--mode[ =]production",
+        "Production webpack build",
+    ),
+    (
+        r"^webpack (serve|dev-server)\b",
+        "Start the webpack dev server",
+    ),
+    (r"^webpack\b", "Bundle with webpack"),
+    (r"^vite( dev)?$", "Start the Vite dev server"),
+    (r"^vite build\b", "Build with Vite"),
+    (r"^next dev\b", "Start the Next.js dev server"),
+    (r"^next build\b", "Build the Next.js app"),
+    (r"^next start\b", "Serve the built Next.js app"),
+    (r"^nodemon\b", "Run with auto-restart on changes"),
+    (r"^rimraf\b", "Delete build output"),
+    (r"^husky\b", "Set up git hooks"),
+];
+
+pub fn built_in_rules() -> Vec<SummaryRule> {
+    BUILT_IN
+        .iter()
+        .map(|(pattern, summary)| SummaryRule {
+            pattern: Regex::new(pattern).expect("built-in patterns are valid"),
+            summary: summary.to_string(),
+        })
+        .collect()
+}
+
+/// The summary of the first rule matching `command`, ignoring any leading
+/// `NAME=value` assignments
+pub fn summarize_command(command: &str, rules: &[SummaryRule]) -> Option<String> {
+    let (_, command) = shell::split_env_prefix(command);
+    rules
+        .iter()
+        .find(|rule| rule.pattern.is_match(&command))
+        .map(|rule| rule.summary.clone())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_summarize_command() {
+        let rules = built_in_rules();
+        let summary = |command| summarize_command(command, &rules);
+
+        assert_eq!(
+            summary("jest --coverage").as_deref(),
+            Some("Run Jest tests")
+        );
+        assert_eq!(
+            summary("NODE_ENV=production webpack --mode production").as_deref(),
+            Some("Production webpack build")
+        );
+        assert_eq!(summary("webpack").as_deref(), Some("Bundle with webpack"));
+        assert_eq!(
+            summary("tsc --noEmit").as_deref(),
+            Some("Type-check with TypeScript")
+        );
+        assert_eq!(summary("node scripts/release.js"), None);
+        assert_eq!(summary("jester"), None);
+    }
+
+    #[test]
+    fn test_configured_rules_come_first() {
+        let mut rules: Vec<SummaryRule> =
+            serde_json::from_str(r#"[{ "pattern": "^jest .*e2e", "summary": "Run e2e tests" }]"#)
+                .unwrap();
+        rules.extend(built_in_rules());
+
+        assert_eq!(
+            summarize_command("jest -c e2e.config.js", &rules).as_deref(),
+            Some("Run e2e tests")
+        );
+        assert_eq!(
+            summarize_command("jest", &rules).as_deref(),
+            Some("Run Jest tests")
+        );
+    }
+}
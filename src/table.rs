@@ -0,0 +1,122 @@
+use std::io::{self, Write};
+
+/// Border drawn around the rows of the table format
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum TableStyle {
+    /// Box-drawing lines (─ │ ┌)
+    Unicode,
+    /// Plain ASCII (- | +), for terminals without box-drawing glyphs
+    Ascii,
+    /// Double lines (═ ║ ╔)
+    Double,
+    /// No border
+    None,
+}
+
+/// The horizontal line piece
+pub fn border_char(style: TableStyle) -> &'static str {
+    match style {
+        TableStyle::Unicode => "─",
+        TableStyle::Ascii => "-",
+        TableStyle::Double => "═",
+        TableStyle::None => "",
+    }
+}
+
+pub fn vertical_char(style: TableStyle) -> &'static str {
+    match style {
+        TableStyle::Unicode => "│",
+        TableStyle::Ascii => "|",
+        TableStyle::Double => "║",
+        TableStyle::None => "",
+    }
+}
+
+/// Top-left, top-right, bottom-left and bottom-right corners
+pub fn corner_chars(style: TableStyle) -> [&'static str; 4] {
+    match style {
+        TableStyle::Unicode => ["┌", "┐", "└", "┘"],
+        TableStyle::Ascii => ["+", "+", "+", "+"],
+        TableStyle::Double => ["╔", "╗", "╚", "╝"],
+        TableStyle::None => ["", "", "", ""],
+    }
+}
+
+/// Columns a border adds to each row
+pub fn extra_width(style: TableStyle) -> usize {
+    match style {
+        TableStyle::None => 0,
+        _ => "│  │".chars().count() - 1,
+    }
+}
+
+/// Write `rows`, each given with its width on screen (escape codes don't
+/// count), indented under the section title and framed in `style`'s border
+pub fn write_rows(
+    out: &mut impl Write,
+    rows: &[(String, usize)],
+    style: TableStyle,
+) -> io::Result<()> {
+    if style == TableStyle::None {
+        for (row, _) in rows {
+            writeln!(out, "    {}", row)?;
+        }
+        return Ok(());
+    }
+
+    let width = rows.iter().map(|(_, width)| *width).max().unwrap_or(0);
+    let rule = border_char(style).repeat(width + 2);
+    let [top_left, top_right, bottom_left, bottom_right] = corner_chars(style);
+    let vertical = vertical_char(style);
+
+    writeln!(out, "   {}{}{}", top_left, rule, top_right)?;
+    for (row, row_width) in rows {
+        let padding = width - row_width;
+        writeln!(out, "   {} {}{:padding$} {}", vertical, row, "", vertical)?;
+    }
+    writeln!(out, "   {}{}{}", bottom_left, rule, bottom_right)?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_border_chars() {
+        assert_eq!(border_char(TableStyle::Unicode), "─");
+        assert_eq!(border_char(TableStyle::Ascii), "-");
+        assert_eq!(border_char(TableStyle::Double), "═");
+        assert_eq!(border_char(TableStyle::None), "");
+        assert_eq!(vertical_char(TableStyle::Ascii), "|");
+        assert_eq!(vertical_char(TableStyle::Double), "║");
+        assert_eq!(corner_chars(TableStyle::Ascii), ["+"; 4]);
+        assert_eq!(corner_chars(TableStyle::Double)[0], "╔");
+    }
+
+    #[test]
+    fn test_write_rows() {
+        let rows = [
+            ("- build : tsc".to_string(), 13),
+            ("- test  : jest -i".to_string(), 17),
+        ];
+        let render = |style| {
+            let mut out = Vec::new();
+            write_rows(&mut out, &rows, style).unwrap();
+            String::from_utf8(out).unwrap()
+        };
+
+        assert_eq!(
+            render(TableStyle::None),
+            "    - build : tsc\n    - test  : jest -i\n"
+        );
+        assert_eq!(
+            render(TableStyle::Ascii),
+            "   +-------------------+\n\
+             \x20  | - build : tsc     |\n\
+             \x20  | - test  : jest -i |\n\
+             \x20  +-------------------+\n"
+        );
+        assert!(render(TableStyle::Double).starts_with("   ╔═══"));
+    }
+}
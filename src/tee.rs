@@ -0,0 +1,54 @@
+use std::io::{self, Write};
+
+/// Writes everything to two destinations, like `tee(1)`: `--tee` pairs the
+/// file with stdout. The first gets each write before the second, so the file
+/// is complete even if stdout's reader goes away.
+pub struct TeeWriter<A, B> {
+    first: A,
+    second: B,
+}
+
+impl<A: Write, B: Write> TeeWriter<A, B> {
+    pub fn new(first: A, second: B) -> Self {
+        TeeWriter { first, second }
+    }
+}
+
+impl<A: Write, B: Write> Write for TeeWriter<A, B> {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        self.first.write_all(buf)?;
+        self.second.write_all(buf)?;
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.first.flush()?;
+        self.second.flush()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_tee_writes_both() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("scripts.txt");
+        let mut stdout = Vec::new();
+
+        let file = fs::File::create(&path).unwrap();
+        let mut tee = TeeWriter::new(file, &mut stdout);
+        write!(tee, "\n   app\n\n").unwrap();
+        tee.write_all("    - build : tsc → dist\n".as_bytes())
+            .unwrap();
+        tee.flush().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap(), stdout);
+        assert_eq!(
+            String::from_utf8(stdout).unwrap(),
+            "\n   app\n\n    - build : tsc → dist\n"
+        );
+    }
+}
@@ -0,0 +1,228 @@
+use anyhow::{bail, Context, Result};
+use serde_json::{json, Value};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::package_manager::PackageManager;
+
+/// Where VS Code looks for the tasks of the workspace in `dir`
+pub fn tasks_file(dir: &Path) -> PathBuf {
+    dir.join(".vscode").join("tasks.json")
+}
+
+/// Add a shell task per script to the tasks.json in `dir`, creating it if
+/// needed. Tasks already there, including ones with the same label as a
+/// script, are left alone; comments in the file are not kept. Returns how
+/// many tasks were added.
+pub fn export_tasks(
+    dir: &Path,
+    scripts: &[(String, String)],
+    manager: PackageManager,
+) -> Result<usize> {
+    let path = tasks_file(dir);
+    let existing = match fs::read_to_string(&path) {
+        Ok(content) => {
+            let parsed = serde_json::from_str(&strip_comments(&content));
+            Some(parsed.with_context(|| format!("Failed to parse {}", path.display()))?)
+        }
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => None,
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+
+    let (tasks, added) = merge_tasks(existing, scripts, manager)?;
+    let dir = path.parent().unwrap();
+    fs::create_dir_all(dir).with_context(|| format!("Failed to create {}", dir.display()))?;
+    fs::write(&path, serde_json::to_string_pretty(&tasks)? + "\n")
+        .with_context(|| format!("Failed to write {}", path.display()))?;
+    Ok(added)
+}
+
+/// `existing` tasks.json (if any) with a task appended for each script not
+/// already there by label
+pub fn merge_tasks(
+    existing: Option<Value>,
+    scripts: &[(String, String)],
+    manager: PackageManager,
+) -> Result<(Value, usize)> {
+    let mut tasks = existing.unwrap_or_else(|| json!({ "version": "2.0.0" }));
+    let Some(object) = tasks.as_object_mut() else {
+        bail!("tasks.json should contain a JSON object");
+    };
+    let list = object.entry("tasks").or_insert_with(|| json!([]));
+    let Some(list) = list.as_array_mut() else {
+        bail!("`tasks` in tasks.json should be an array");
+    };
+
+    let mut added = 0;
+    for (name, _) in scripts {
+        let labelled = |task: &Value| task["label"].as_str() == Some(name);
+        if list.iter().any(labelled) {
+            continue;
+        }
+        let command = manager.command_line(name);
+        list.push(json!({
+            "label": name,
+            "type": "shell",
+            "command": command,
+            "problemMatcher": [],
+        }));
+        added += 1;
+    }
+    Ok((tasks, added))
+}
+
+/// A complete tasks.json with an `npm` task per script, grouped under
+/// `test` if the name mentions it and `build` otherwise. Unlike
+/// `export_tasks`, VS Code picks the package manager for these itself.
+pub fn npm_tasks(scripts: &[(String, String)]) -> Value {
+    let tasks: Vec<Value> = scripts
+        .iter()
+        .map(|(name, _)| {
+            let kind = if name.contains("test") {
+                "test"
+            } else {
+                "build"
+            };
+            json!({
+                "type": "npm",
+                "script": name,
+                "label": format!("npm: {}", name),
+                "group": { "kind": kind, "isDefault": false },
+                "problemMatcher": [],
+            })
+        })
+        .collect();
+    json!({ "version": "2.0.0", "tasks": tasks })
+}
+
+// VS Code allows // and /* */ comments in tasks.json; drop them (outside
+// strings) so it parses as plain JSON
+fn strip_comments(content: &str) -> String {
+    let mut out = String::new();
+    let mut chars = content.chars().peekable();
+    let mut in_string = false;
+    while let Some(c) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => out.extend(chars.next()),
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match (c, chars.peek()) {
+            ('"', _) => {
+                in_string = true;
+                out.push(c);
+            }
+            ('/', Some('/')) => while chars.next_if(|&c| c != '\n').is_some() {},
+            ('/', Some('*')) => {
+                chars.next();
+                while let Some(c) = chars.next() {
+                    if c == '*' && chars.next_if_eq(&'/').is_some() {
+                        break;
+                    }
+                }
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn scripts(names: &[&str]) -> Vec<(String, String)> {
+        names
+            .iter()
+            .map(|name| (name.to_string(), String::new()))
+            .collect()
+    }
+
+    #[test]
+    fn test_export_tasks_merges() {
+        let dir = tempfile::tempdir().unwrap();
+        fs::create_dir_all(dir.path().join(".vscode")).unwrap();
+        fs::write(
+            tasks_file(dir.path()),
+            r#"{
+                // See https://go.microsoft.com/fwlink/?LinkId=733558
+                "version": "2.0.0",
+                "tasks": [
+                    { "label": "build", "type": "shell", "command": "make /* all */" },
+                    /* kept as is */
+                    { "label": "deploy", "type": "shell", "command": "./deploy.sh" }
+                ]
+            }"#,
+        )
+        .unwrap();
+
+        let added = export_tasks(
+            dir.path(),
+            &scripts(&["build", "test:unit"]),
+            PackageManager::Npm,
+        )
+        .unwrap();
+        assert_eq!(added, 1);
+
+        let content = fs::read_to_string(tasks_file(dir.path())).unwrap();
+        let tasks: Value = serde_json::from_str(&content).unwrap();
+        assert_eq!(tasks["version"], "2.0.0");
+        let labels: Vec<_> = tasks["tasks"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|task| task["label"].as_str().unwrap())
+            .collect();
+        assert_eq!(labels, ["build", "deploy", "test:unit"]);
+        assert_eq!(tasks["tasks"][0]["command"], "make /* all */");
+        assert_eq!(tasks["tasks"][2]["command"], "npm run test:unit");
+    }
+
+    #[test]
+    fn test_merge_tasks_creates_file_contents() {
+        let (tasks, added) = merge_tasks(None, &scripts(&["dev"]), PackageManager::Pnpm).unwrap();
+        assert_eq!(added, 1);
+        assert_eq!(
+            tasks,
+            json!({
+                "version": "2.0.0",
+                "tasks": [
+                    { "label": "dev", "type": "shell", "command": "pnpm run dev", "problemMatcher": [] }
+                ]
+            })
+        );
+        assert!(merge_tasks(Some(json!([])), &[], PackageManager::Npm).is_err());
+    }
+
+    #[test]
+    fn test_npm_tasks() {
+        let tasks = npm_tasks(&scripts(&["build", "test:unit", "lint"]));
+
+        // What the tasks.json schema requires of npm tasks
+        assert_eq!(tasks["version"], "2.0.0");
+        let list = tasks["tasks"].as_array().unwrap();
+        assert_eq!(list.len(), 3);
+        for task in list {
+            assert_eq!(task["type"], "npm");
+            assert!(task["script"].is_string());
+            assert!(["build", "test"].contains(&task["group"]["kind"].as_str().unwrap()));
+            assert_eq!(task["group"]["isDefault"], false);
+            assert!(task["problemMatcher"].is_array());
+        }
+        assert_eq!(
+            list[1],
+            json!({
+                "type": "npm",
+                "script": "test:unit",
+                "label": "npm: test:unit",
+                "group": { "kind": "test", "isDefault": false },
+                "problemMatcher": []
+            })
+        );
+        assert_eq!(list[2]["group"]["kind"], "build");
+    }
+}
@@ -0,0 +1,195 @@
+use anyhow::{Context, Result};
+use notify::{Config, EventKind, PollWatcher, RecommendedWatcher, RecursiveMode, Watcher};
+use std::io::{self, Write};
+use std::path::{Component, Path, PathBuf};
+use std::sync::mpsc;
+use std::time::Duration;
+
+use crate::color::*;
+
+// Editors often write a file in several steps; wait this long for the
+// events to settle before re-rendering
+const DEBOUNCE: Duration = Duration::from_millis(100);
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum WatcherKind {
+    /// inotify / FSEvents / ReadDirectoryChangesW
+    Native,
+    /// Stat the file on an interval, for filesystems (NFS, SMB, some
+    /// container mounts) that don't deliver change events
+    Poll(Duration),
+}
+
+/// Whether `--watch` clears the screen before each render
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Clear {
+    On,
+    /// Keep the earlier renders, each headed by the time it was made
+    Off,
+}
+
+/// `--watch-interval` opts into polling; without it the native backend is used
+pub fn watcher_kind(interval_ms: Option<u64>) -> WatcherKind {
+    match interval_ms {
+        Some(ms) => WatcherKind::Poll(Duration::from_millis(ms)),
+        None => WatcherKind::Native,
+    }
+}
+
+/// Call `render` now and again every time `path` changes. Runs until
+/// interrupted; a failing render is reported and watching carries on.
+pub fn watch(
+    path: &Path,
+    kind: WatcherKind,
+    clear: Clear,
+    mut render: impl FnMut() -> Result<()>,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher: Box<dyn Watcher> = match kind {
+        WatcherKind::Native => Box::new(RecommendedWatcher::new(tx, Config::default())?),
+        WatcherKind::Poll(interval) => Box::new(PollWatcher::new(
+            tx,
+            Config::default().with_poll_interval(interval),
+        )?),
+    };
+
+    // Watch the directory rather than the file itself so that editors which
+    // save by replacing the file don't detach the watch
+    let dir = match path.parent() {
+        Some(dir) if !dir.as_os_str().is_empty() => dir,
+        _ => Path::new("."),
+    };
+    watcher
+        .watch(dir, RecursiveMode::NonRecursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+    let file_name = path.file_name();
+    loop {
+        let time = chrono::Local::now().format("%H:%M:%S").to_string();
+        let mut stdout = io::stdout();
+        let _ = stdout.write_all(before_render(clear, &time).as_bytes());
+        let _ = stdout.flush();
+        if let Err(e) = render() {
+            eprintln!("{}", format!("Error: {:#}", e).red());
+        }
+
+        // Block until the watched file changes. Reading it ourselves shows up
+        // as an access event, so those have to be skipped.
+        loop {
+            let event = rx.recv().context("File watcher stopped")??;
+            let touches_file = event.paths.iter().any(|p| p.file_name() == file_name);
+            if touches_file && !matches!(event.kind, EventKind::Access(_)) {
+                break;
+            }
+        }
+        while rx.recv_timeout(DEBOUNCE).is_ok() {}
+    }
+}
+
+// Dependencies, VCS data and build or test output: changes there don't mean
+// the code under test changed, and test runs write to some of them
+const IGNORED_DIRS: &[&str] = &[
+    "node_modules",
+    ".git",
+    "dist",
+    "build",
+    "coverage",
+    ".cache",
+];
+
+/// Whether a change to `path` should trigger a re-run
+pub fn is_relevant(path: &Path) -> bool {
+    !path.components().any(|component| match component {
+        Component::Normal(name) => IGNORED_DIRS.iter().any(|dir| name == *dir),
+        _ => false,
+    })
+}
+
+/// Call `on_change` with the changed files every time relevant files under
+/// `dir` (an absolute path, as event paths are) change. Runs until
+/// interrupted; a failing call is reported and watching carries on. Changes
+/// made while `on_change` runs (test snapshots, say) are dropped rather than
+/// triggering another round.
+pub fn watch_files(
+    dir: &Path,
+    kind: WatcherKind,
+    mut on_change: impl FnMut(&[PathBuf]) -> Result<()>,
+) -> Result<()> {
+    let (tx, rx) = mpsc::channel();
+    let mut watcher: Box<dyn Watcher> = match kind {
+        WatcherKind::Native => Box::new(RecommendedWatcher::new(tx, Config::default())?),
+        WatcherKind::Poll(interval) => Box::new(PollWatcher::new(
+            tx,
+            Config::default().with_poll_interval(interval),
+        )?),
+    };
+    watcher
+        .watch(dir, RecursiveMode::Recursive)
+        .with_context(|| format!("Failed to watch {}", dir.display()))?;
+
+    let relevant_paths = |event: notify::Event| -> Vec<PathBuf> {
+        if matches!(event.kind, EventKind::Access(_)) {
+            return Vec::new();
+        }
+        let paths = event.paths.into_iter();
+        paths
+            .filter(|p| is_relevant(p.strip_prefix(dir).unwrap_or(p)))
+            .collect()
+    };
+
+    loop {
+        let mut changed = Vec::new();
+        while changed.is_empty() {
+            let event = rx.recv().context("File watcher stopped")??;
+            changed.extend(relevant_paths(event));
+        }
+        while let Ok(event) = rx.recv_timeout(DEBOUNCE) {
+            changed.extend(relevant_paths(event?));
+        }
+        changed.sort();
+        changed.dedup();
+
+        if let Err(e) = on_change(&changed) {
+            eprintln!("{}", format!("Error: {:#}", e).red());
+        }
+        while rx.try_recv().is_ok() {}
+    }
+}
+
+// The screen clear, or with `Clear::Off` a separator with the time
+fn before_render(clear: Clear, time: &str) -> String {
+    match clear {
+        Clear::On => "\x1b[2J\x1b[H".to_string(),
+        Clear::Off => format!("{}\n", format!("--- {} ---", time).dimmed()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_watcher_kind() {
+        assert_eq!(watcher_kind(None), WatcherKind::Native);
+        assert_eq!(
+            watcher_kind(Some(500)),
+            WatcherKind::Poll(Duration::from_millis(500))
+        );
+    }
+
+    #[test]
+    fn test_before_render() {
+        control::set_override(false);
+        assert_eq!(before_render(Clear::On, "12:00:00"), "\x1b[2J\x1b[H");
+        assert_eq!(before_render(Clear::Off, "12:00:00"), "--- 12:00:00 ---\n");
+    }
+
+    #[test]
+    fn test_is_relevant() {
+        assert!(is_relevant(Path::new("src/app.test.ts")));
+        assert!(is_relevant(Path::new("builder/index.js")));
+        assert!(!is_relevant(Path::new("node_modules/jest/bin/jest.js")));
+        assert!(!is_relevant(Path::new("packages/app/dist/index.js")));
+        assert!(!is_relevant(Path::new(".git/index")));
+    }
+}
@@ -0,0 +1,170 @@
+use anyhow::{Context, Result};
+use serde::Deserialize;
+use std::collections::BTreeSet;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// pnpm's own list of workspaces, next to the root package.json
+pub const PNPM_WORKSPACE_FILE: &str = "pnpm-workspace.yaml";
+
+/// The `workspaces` key of a root package.json: either a list of globs
+/// (npm, bun) or yarn's `{ "packages": [...] }` form
+#[derive(Debug, Deserialize)]
+#[serde(untagged)]
+pub enum Workspaces {
+    Patterns(Vec<String>),
+    Config {
+        #[serde(default)]
+        packages: Vec<String>,
+    },
+}
+
+impl Workspaces {
+    pub fn patterns(&self) -> &[String] {
+        match self {
+            Workspaces::Patterns(patterns) => patterns,
+            Workspaces::Config { packages } => packages,
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct PnpmWorkspace {
+    #[serde(default)]
+    packages: Vec<String>,
+}
+
+/// The workspace patterns of the root in `root_dir`, from its package.json's
+/// `workspaces` or failing that pnpm-workspace.yaml, along with the name of
+/// the file they came from. `None` outside a monorepo root.
+pub fn declared_patterns(
+    root_dir: &Path,
+    workspaces: Option<&Workspaces>,
+) -> Result<Option<(Vec<String>, &'static str)>> {
+    if let Some(workspaces) = workspaces {
+        return Ok(Some((workspaces.patterns().to_vec(), "package.json")));
+    }
+    let path = root_dir.join(PNPM_WORKSPACE_FILE);
+    let content = match fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(None),
+        Err(e) => return Err(e).with_context(|| format!("Failed to read {}", path.display())),
+    };
+    // An empty file is valid YAML, and null
+    let pnpm: Option<PnpmWorkspace> = serde_yaml::from_str(&content)
+        .with_context(|| format!("Failed to parse {}", path.display()))?;
+    Ok(Some((
+        pnpm.unwrap_or_default().packages,
+        PNPM_WORKSPACE_FILE,
+    )))
+}
+
+/// What to do with workspace packages that have no scripts to show
+#[derive(Clone, Copy, Debug, PartialEq, Eq, clap::ValueEnum)]
+pub enum Empty {
+    /// Leave them out
+    Skip,
+    /// List them like any other package
+    Show,
+    /// Collect them in a "no scripts" section at the end
+    Warn,
+}
+
+/// package.json of every workspace member under `root_dir`, in path order.
+/// Patterns starting with `!` exclude what they match.
+pub fn member_manifests(root_dir: &Path, patterns: &[String]) -> Result<Vec<PathBuf>> {
+    let mut included = BTreeSet::new();
+    let mut excluded = BTreeSet::new();
+    for pattern in patterns {
+        let (set, pattern) = match pattern.strip_prefix('!') {
+            Some(pattern) => (&mut excluded, pattern),
+            None => (&mut included, pattern.as_str()),
+        };
+        let full = root_dir.join(pattern.trim_end_matches('/'));
+        let paths = glob::glob(&full.to_string_lossy())
+            .with_context(|| format!("Invalid workspace pattern `{}`", pattern))?;
+        set.extend(paths.filter_map(Result::ok).filter(|dir| dir.is_dir()));
+    }
+
+    Ok(included
+        .difference(&excluded)
+        .map(|dir| dir.join("package.json"))
+        .filter(|manifest| manifest.is_file())
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+
+    #[test]
+    fn test_member_manifests() {
+        let dir = tempfile::tempdir().unwrap();
+        for member in [
+            "packages/app",
+            "packages/lib",
+            "packages/legacy",
+            "tools/cli",
+        ] {
+            fs::create_dir_all(dir.path().join(member)).unwrap();
+            fs::write(dir.path().join(member).join("package.json"), "{}").unwrap();
+        }
+        fs::create_dir_all(dir.path().join("packages/no-manifest")).unwrap();
+
+        let patterns = ["packages/*", "!packages/legacy", "tools/cli/"].map(String::from);
+        let manifests = member_manifests(dir.path(), &patterns).unwrap();
+        let members: Vec<_> = manifests
+            .iter()
+            .map(|m| m.strip_prefix(dir.path()).unwrap().to_str().unwrap())
+            .collect();
+        assert_eq!(
+            members,
+            [
+                "packages/app/package.json",
+                "packages/lib/package.json",
+                "tools/cli/package.json",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_yarn_workspaces_config() {
+        let workspaces: Workspaces =
+            serde_json::from_str(r#"{ "packages": ["packages/*"], "nohoist": [] }"#).unwrap();
+        assert_eq!(workspaces.patterns(), ["packages/*"]);
+    }
+
+    #[test]
+    fn test_declared_patterns() {
+        let dir = tempfile::tempdir().unwrap();
+        assert_eq!(declared_patterns(dir.path(), None).unwrap(), None);
+
+        fs::write(
+            dir.path().join(PNPM_WORKSPACE_FILE),
+            "packages:\n  - 'apps/*'\n  - '!apps/legacy'\n",
+        )
+        .unwrap();
+        assert_eq!(
+            declared_patterns(dir.path(), None).unwrap(),
+            Some((
+                vec!["apps/*".to_string(), "!apps/legacy".to_string()],
+                PNPM_WORKSPACE_FILE
+            ))
+        );
+
+        // package.json's own list wins
+        let workspaces = Workspaces::Patterns(vec!["packages/*".to_string()]);
+        let (patterns, source) = declared_patterns(dir.path(), Some(&workspaces))
+            .unwrap()
+            .unwrap();
+        assert_eq!(
+            (patterns.as_slice(), source),
+            (workspaces.patterns(), "package.json")
+        );
+
+        fs::write(dir.path().join(PNPM_WORKSPACE_FILE), "").unwrap();
+        let (patterns, _) = declared_patterns(dir.path(), None).unwrap().unwrap();
+        assert!(patterns.is_empty());
+    }
+}
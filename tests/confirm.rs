@@ -0,0 +1,42 @@
+use std::fs;
+use std::process::{Command, Stdio};
+
+// A package whose `clean` looks destructive, run with stdin closed the way CI
+// runs it
+fn run(args: &[&str]) -> std::process::Output {
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{ "name": "app", "scripts": { "clean": "rm -rf dist", "build": "tsc" } }"#,
+    )
+    .unwrap();
+    Command::new(env!("CARGO_BIN_EXE_sl"))
+        .current_dir(dir.path())
+        .args(args)
+        .stdin(Stdio::null())
+        .output()
+        .unwrap()
+}
+
+#[test]
+fn test_destructive_script_without_a_terminal_fails() {
+    let output = run(&["--run", "clean"]);
+    assert!(!output.status.success());
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("refusing to run `clean` without --yes: stdin is not a terminal"));
+}
+
+#[test]
+fn test_preview_without_a_terminal_fails() {
+    let output = run(&["--run", "build", "--preview"]);
+    assert!(!output.status.success());
+    let stdout = String::from_utf8_lossy(&output.stdout);
+    assert!(stdout.contains("tsc"));
+    assert!(String::from_utf8_lossy(&output.stderr)
+        .contains("refusing to run `build` without --yes: stdin is not a terminal"));
+
+    // --dry-run has nothing to confirm
+    assert!(run(&["--run", "build", "--preview", "--dry-run"])
+        .status
+        .success());
+}
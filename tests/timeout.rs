@@ -0,0 +1,37 @@
+use std::fs;
+use std::process::Command;
+use std::time::{Duration, Instant};
+
+// `--run` goes through the real package manager, so this needs npm
+fn have_npm() -> bool {
+    Command::new("npm").arg("--version").output().is_ok()
+}
+
+#[test]
+fn test_timeout_kills_a_sleeping_script() {
+    if !have_npm() {
+        eprintln!("npm not found, skipping");
+        return;
+    }
+    let dir = tempfile::tempdir().unwrap();
+    fs::write(
+        dir.path().join("package.json"),
+        r#"{ "name": "nap", "scripts": { "nap": "sleep 30", "quick": "exit 0" } }"#,
+    )
+    .unwrap();
+    let sl = |script: &str| {
+        Command::new(env!("CARGO_BIN_EXE_sl"))
+            .current_dir(dir.path())
+            .args(["--run", script, "--timeout", "1"])
+            .output()
+            .unwrap()
+    };
+
+    let started = Instant::now();
+    let output = sl("nap");
+    assert_eq!(output.status.code(), Some(124));
+    assert!(started.elapsed() < Duration::from_secs(20));
+    assert!(String::from_utf8_lossy(&output.stderr).contains("timed out after 1s"));
+
+    assert_eq!(sl("quick").status.code(), Some(0));
+}